@@ -44,3 +44,26 @@ macro_rules! day {
         };
     };
 }
+
+/// Builds a nom parser from a format string, with each `{}` filled by a
+/// [`FormatField`](crate::utils::parser::FormatField) of the given type.
+///
+/// `format_parser!("p={},{} v={},{}"; u8, u8, i8, i8)` produces a tuple,
+/// while `format_parser!("{}: {}" => Eqn { result: u64, nums: SmallVec<[u16; 12]> })`
+/// produces the struct with its fields filled in order.
+macro_rules! format_parser {
+    ($fmt:literal; $($ty:ty),+ $(,)?) => {{
+        const _: () = assert!(
+            $crate::utils::parser::placeholders($fmt) == [$(stringify!($ty)),+].len(),
+            "Wrong number of fields for format string",
+        );
+        match $crate::utils::parser::FormatParser::<($($ty,)+), _>::new($fmt) {
+            Ok(parser) => parser,
+            // The number of fields was checked at compile time
+            Err(_) => unreachable!(),
+        }
+    }};
+    ($fmt:literal => $name:ident { $($field:ident: $ty:ty),+ $(,)? }) => {
+        nom::Parser::map(format_parser!($fmt; $($ty),+), |($($field,)+)| $name { $($field),+ })
+    };
+}
@@ -42,6 +42,13 @@ pub fn get_small() -> bool {
     })
 }
 
+/// Whether `name` was passed as a command line argument
+pub fn has_arg(name: &str) -> bool {
+    std::env::args()
+        .skip(1)
+        .any(|arg| arg.eq_ignore_ascii_case(name))
+}
+
 mod prelude {
     pub type GenError = Box<dyn std::error::Error>;
     pub type GenResult<T = ()> = Result<T, GenError>;
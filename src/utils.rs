@@ -9,6 +9,22 @@ use snafu::Snafu;
 mod eof_iterator;
 pub use eof_iterator::{eof_iterator, EofParserIterator};
 
+pub mod bits;
+pub mod cycle;
+pub mod dot;
+pub mod interval;
+pub mod memo;
+pub mod numtheory;
+pub mod ocr;
+pub mod parser;
+pub mod record;
+pub mod region;
+pub mod render;
+pub mod search;
+pub mod stream;
+pub mod view;
+pub mod vm;
+
 use std::{
     fmt,
     fs::File,
@@ -45,6 +61,14 @@ pub fn load_input_bytes(day: Day) -> io::Result<Vec<u8>> {
     Ok(input)
 }
 
+/// Opens the input for `day`, parsing records with `parser` as they are read
+pub fn load_records<O, F>(day: Day, parser: F) -> io::Result<stream::Records<BufReader<File>, O, F>>
+where
+    F: for<'a> FnMut(&'a [u8]) -> nom::IResult<&'a [u8], O>,
+{
+    Ok(stream::records(load_input(day)?, parser))
+}
+
 pub fn process_inputs<F, R>(day: Day, f: F) -> io::Result<R>
 where
     F: FnOnce(ProcessResults<'_, Lines<BufReader<File>>, io::Error>) -> R,
@@ -0,0 +1,184 @@
+use snafu::{ensure, OptionExt, Snafu};
+
+/// Decodes a string of hex digits into bytes, padding an odd final digit
+/// with zero bits
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, BitsError> {
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8).context(HexSnafu { c }))
+        .collect::<Result<Vec<u8>, _>>()?;
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect())
+}
+
+/// Cursor over the bits of a byte slice, reading the most significant bit
+/// of each byte first
+#[derive(Debug, Clone, Copy)]
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            end: data.len() * 8,
+        }
+    }
+
+    /// Number of bits read so far
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bits left to read
+    pub fn remaining(&self) -> usize {
+        self.end - self.pos
+    }
+
+    /// Whether every bit has been read
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.end
+    }
+
+    /// Reads a `count` bit unsigned number, most significant bit first
+    pub fn read(&mut self, count: u32) -> Result<u64, BitsError> {
+        ensure!(count <= u64::BITS, WidthSnafu { count });
+        self.need(count as usize)?;
+        let mut val = 0;
+        for _ in 0..count {
+            let bit = self.data[self.pos / 8] >> (7 - self.pos % 8) & 1;
+            val = val << 1 | u64::from(bit);
+            self.pos += 1;
+        }
+        Ok(val)
+    }
+
+    /// Reads a single bit
+    pub fn read_bool(&mut self) -> Result<bool, BitsError> {
+        self.read(1).map(|b| b == 1)
+    }
+
+    /// Splits off the next `len` bits as their own reader, skipping them in
+    /// this one
+    pub fn split(&mut self, len: usize) -> Result<BitReader<'a>, BitsError> {
+        self.need(len)?;
+        let sub = BitReader {
+            data: self.data,
+            pos: self.pos,
+            end: self.pos + len,
+        };
+        self.pos += len;
+        Ok(sub)
+    }
+
+    /// Reads a variable-length number made of `group` bit chunks, each
+    /// preceded by a bit which is set if another chunk follows
+    pub fn read_literal(&mut self, group: u32) -> Result<u64, BitsError> {
+        ensure!(group > 0 && group < u64::BITS, WidthSnafu { count: group });
+        let mut val = 0u64;
+        loop {
+            let more = self.read_bool()?;
+            let chunk = self.read(group)?;
+            ensure!(val >> (u64::BITS - group) == 0, OverflowSnafu);
+            val = val << group | chunk;
+            if !more {
+                return Ok(val);
+            }
+        }
+    }
+
+    fn need(&self, count: usize) -> Result<(), BitsError> {
+        let remaining = self.remaining();
+        ensure!(count <= remaining, EofSnafu { needed: count, remaining });
+        Ok(())
+    }
+}
+
+/// Builds a byte buffer bit by bit, in the layout read by [`BitReader`]
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bits written
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no bits were written
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes the low `count` bits of `val`, most significant bit first
+    pub fn write(&mut self, val: u64, count: u32) {
+        assert!(count <= u64::BITS, "Cannot write {count} bits at once");
+        for i in (0..count).rev() {
+            if self.len == self.bytes.len() * 8 {
+                self.bytes.push(0);
+            }
+            let bit = (val >> i & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+
+    /// Writes a single bit
+    pub fn write_bool(&mut self, bit: bool) {
+        self.write(u64::from(bit), 1);
+    }
+
+    /// Writes `val` in the variable-length format read by
+    /// [`BitReader::read_literal`]
+    pub fn write_literal(&mut self, val: u64, group: u32) {
+        assert!(group > 0 && group < u64::BITS, "Invalid literal group size {group}");
+        let bits = u64::BITS - val.leading_zeros();
+        let chunks = bits.div_ceil(group).max(1);
+        for i in (0..chunks).rev() {
+            self.write_bool(i > 0);
+            self.write(val >> (i * group) & ((1 << group) - 1), group);
+        }
+    }
+
+    /// Appends every bit from another writer
+    pub fn append(&mut self, other: &BitWriter) {
+        let mut bits = BitReader::new(&other.bytes);
+        for _ in 0..other.len {
+            self.write_bool(bits.read_bool().unwrap());
+        }
+    }
+
+    /// The written bytes, with the final byte padded with zero bits
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The written bytes as upper case hex digits
+    pub fn to_hex(&self) -> String {
+        self.bytes.iter().map(|b| format!("{b:02X}")).collect()
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum BitsError {
+    #[snafu(display("Needed {needed} bits but only {remaining} remain"))]
+    Eof { needed: usize, remaining: usize },
+    #[snafu(display("Cannot read {count} bits at once"))]
+    Width { count: u32 },
+    #[snafu(display("Literal does not fit in 64 bits"))]
+    Overflow,
+    #[snafu(display("Invalid hex digit {c:?}"))]
+    Hex { c: char },
+}
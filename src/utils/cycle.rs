@@ -0,0 +1,236 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+};
+
+use indexmap::IndexSet;
+use num::PrimInt;
+
+/// A cycle in a sequence of states, where every state from step `start`
+/// onwards repeats every `len` steps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as step `n`
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+
+    /// Extrapolates a value which grows by the same amount every cycle, such
+    /// as a height, to step `n`. `history` holds the value at each step from
+    /// 0 up to and including `start + len`, one whole cycle past the start.
+    ///
+    /// Returns `None` if `history` is shorter than that, or on overflow.
+    pub fn extrapolate<T: PrimInt>(&self, n: usize, history: &[T]) -> Option<T> {
+        if history.len() <= self.start + self.len {
+            return None;
+        }
+        let base = self.reduce(n);
+        if base == n {
+            return Some(history[n]);
+        }
+        let per_cycle = history[self.start + self.len] - history[self.start];
+        let cycles = T::from((n - base) / self.len)?;
+        history[base].checked_add(&per_cycle.checked_mul(&cycles)?)
+    }
+}
+
+/// Finds the cycle reached by repeatedly applying `step` to `init`, using
+/// Brent's algorithm to only keep two states at a time.
+///
+/// Never returns if the states don't repeat.
+pub fn brent<S, F>(init: S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    // Find the length with a tortoise teleporting to the hare at each
+    // power of two
+    let (mut power, mut len) = (1, 1);
+    let mut tortoise = init.clone();
+    let mut hare = step(&init);
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // Find the start with the hare `len` steps ahead
+    let mut tortoise = init.clone();
+    let mut hare = init;
+    for _ in 0..len {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// Finds the cycle reached by repeatedly applying `step` to `init`, using
+/// Floyd's tortoise and hare.
+///
+/// Never returns if the states don't repeat.
+pub fn floyd<S, F>(init: S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    // Meet somewhere within the cycle, with the hare moving twice as fast
+    let mut tortoise = step(&init);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    // The meeting point is a whole number of cycles from the start
+    let mut start = 0;
+    tortoise = init;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut len = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        len += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// Notices the first repeat in a sequence of states as they are visited,
+/// for simulations which need more than a step function, or whose states
+/// are summarized by a key
+#[derive(Debug, Clone)]
+pub struct Detector<K> {
+    seen: HashMap<K, usize>,
+    steps: usize,
+}
+
+impl<K: Hash + Eq> Detector<K> {
+    pub fn new() -> Self {
+        Self { seen: HashMap::new(), steps: 0 }
+    }
+
+    /// Number of states visited
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Records the state at the next step, returning the cycle if the same
+    /// state was visited before
+    pub fn visit(&mut self, key: K) -> Option<Cycle> {
+        let step = self.steps;
+        self.steps += 1;
+        match self.seen.entry(key) {
+            Entry::Occupied(entry) => Some(Cycle { start: *entry.get(), len: step - entry.get() }),
+            Entry::Vacant(entry) => {
+                entry.insert(step);
+                None
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq> Default for Detector<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state after applying `step` to `init` `n` times, skipping whole
+/// cycles once a state repeats
+pub fn nth_state<S, F>(init: S, n: usize, mut step: F) -> S
+where
+    S: Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut states = IndexSet::new();
+    let mut cur = init;
+    for i in 0..n {
+        let (start, inserted) = states.insert_full(cur);
+        if !inserted {
+            let cycle = Cycle { start, len: i - start };
+            return states.swap_remove_index(cycle.reduce(n)).expect("Cycle is within seen states");
+        }
+        cur = step(&states[i]);
+    }
+    cur
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0, 1, 2, 3 then 4..=9 repeating, with a start of 4 and a length of 6
+    fn step(&n: &u32) -> u32 {
+        if n == 9 {
+            4
+        } else {
+            n + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle { start: 4, len: 6 };
+
+    #[test]
+    fn detectors() {
+        assert_eq!(brent(0, step), CYCLE);
+        assert_eq!(floyd(0, step), CYCLE);
+        assert_eq!(brent(7, step), Cycle { start: 0, len: 6 });
+        assert_eq!(floyd(5, |&n| n), Cycle { start: 0, len: 1 });
+
+        let mut detector = Detector::new();
+        let mut state = 0;
+        let cycle = loop {
+            if let Some(cycle) = detector.visit(state) {
+                break cycle;
+            }
+            state = step(&state);
+        };
+        assert_eq!(cycle, CYCLE);
+        assert_eq!(detector.steps(), 11);
+    }
+
+    #[test]
+    fn reduce_and_nth() {
+        assert_eq!(CYCLE.reduce(3), 3);
+        assert_eq!(CYCLE.reduce(10), 4);
+        assert_eq!(CYCLE.reduce(1_000_001), 5);
+        assert_eq!(nth_state(0, 3, step), 3);
+        assert_eq!(nth_state(0, 1_000_001, step), 5);
+    }
+
+    #[test]
+    fn extrapolate() {
+        // Grows by 10 over each cycle after a lead-in
+        let history: Vec<u64> = (0..=10).map(|i| if i < 4 { i } else { 4 + (i - 4) / 6 * 10 + (i - 4) % 6 }).collect();
+        assert_eq!(CYCLE.extrapolate(2, &history), Some(2));
+        assert_eq!(CYCLE.extrapolate(10, &history), Some(14));
+        assert_eq!(CYCLE.extrapolate(17, &history), Some(25));
+        assert_eq!(CYCLE.extrapolate(17, &history[..10]), None);
+        assert_eq!(CYCLE.extrapolate(usize::MAX, &history.iter().map(|&h| h as u8).collect::<Vec<_>>()), None);
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::{self, Display, Write};
+
+/// Builds a Graphviz DOT description of a graph, with nodes identified by
+/// index and labelled with any displayable value.
+///
+/// The output can be rendered with e.g. `dot -Tsvg`.
+#[derive(Debug, Clone)]
+pub struct Dot {
+    buf: String,
+    arrow: &'static str,
+}
+
+impl Dot {
+    /// Starts a graph with directed edges
+    pub fn digraph(name: &str) -> Self {
+        Self::start("digraph", name, "->")
+    }
+
+    /// Starts a graph with undirected edges
+    pub fn graph(name: &str) -> Self {
+        Self::start("graph", name, "--")
+    }
+
+    fn start(kind: &str, name: &str, arrow: &'static str) -> Self {
+        let mut dot = Self { buf: String::new(), arrow };
+        dot.line(format_args!("{kind} {} {{", Quoted(name)));
+        dot
+    }
+
+    /// Adds a node, with extra attributes such as `("shape", "box")`
+    pub fn node(&mut self, id: usize, label: impl Display, attrs: &[(&str, &str)]) -> &mut Self {
+        self.line(format_args!("    n{id} [label={}{}];", Quoted(label), Attrs(attrs)))
+    }
+
+    /// Adds an edge without a label
+    pub fn edge(&mut self, from: usize, to: usize) -> &mut Self {
+        let arrow = self.arrow;
+        self.line(format_args!("    n{from} {arrow} n{to};"))
+    }
+
+    /// Adds an edge labelled with e.g. the condition for following it
+    pub fn labelled_edge(&mut self, from: usize, to: usize, label: impl Display) -> &mut Self {
+        let arrow = self.arrow;
+        self.line(format_args!("    n{from} {arrow} n{to} [label={}];", Quoted(label)))
+    }
+
+    /// Closes the graph, returning its description
+    pub fn finish(mut self) -> String {
+        self.line(format_args!("}}"));
+        self.buf
+    }
+
+    fn line(&mut self, args: fmt::Arguments<'_>) -> &mut Self {
+        self.buf.write_fmt(args).expect("Writing to a String cannot fail");
+        self.buf.push('\n');
+        self
+    }
+}
+
+/// Displays a value as a DOT quoted string
+struct Quoted<T>(T);
+
+impl<T: Display> Display for Quoted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.to_string().chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{c}")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+struct Attrs<'a>(&'a [(&'a str, &'a str)]);
+
+impl Display for Attrs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, val) in self.0 {
+            write!(f, ", {key}={}", Quoted(val))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,652 @@
+use std::{fmt, ops::Range};
+
+use num::{
+    traits::{WrappingAdd, WrappingNeg, WrappingSub},
+    PrimInt,
+};
+
+/// A half-open interval `start..end`, which is empty unless `start < end`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    pub const fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    /// Interval covering `start..=end`, or `None` if `end` is the largest
+    /// value of `T`, as the exclusive end wouldn't fit
+    pub fn inclusive(start: T, end: T) -> Option<Self> {
+        Some(Self::new(start, end.checked_add(&T::one())?))
+    }
+
+    /// Interval covering `len` values from `start`
+    pub fn with_len(start: T, len: T) -> Self {
+        Self::new(start, start + len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// Number of values covered
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        self.start <= val && val < self.end
+    }
+
+    /// Whether every value of `other` is in this interval, which is always
+    /// true for an empty `other`
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    /// Whether any value is in both intervals
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    /// Values in both intervals, which may be empty
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Splits into the values below `at` and those from `at` onwards,
+    /// either of which may be empty
+    pub fn split_at(&self, at: T) -> [Self; 2] {
+        let at = at.clamp(self.start, self.end.max(self.start));
+        [Self::new(self.start, at), Self::new(at, self.end)]
+    }
+
+    /// Values below and above `other`, either of which may be empty
+    pub fn difference(&self, other: &Self) -> [Self; 2] {
+        if other.is_empty() {
+            return [*self, Self::new(self.end, self.end)];
+        }
+        let [below, rest] = self.split_at(other.start);
+        let [_, above] = rest.split_at(other.end);
+        [below, above]
+    }
+
+    /// Moves both ends by `offset`, which wraps to allow moving down for
+    /// unsigned types
+    pub fn shift(&self, offset: T) -> Self
+    where
+        T: WrappingAdd,
+    {
+        Self::new(self.start.wrapping_add(&offset), self.end.wrapping_add(&offset))
+    }
+}
+
+impl<T> From<Range<T>> for Interval<T> {
+    fn from(range: Range<T>) -> Self {
+        Self { start: range.start, end: range.end }
+    }
+}
+
+impl<T> From<Interval<T>> for Range<T> {
+    fn from(interval: Interval<T>) -> Self {
+        interval.start..interval.end
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// A set of values stored as sorted intervals, which are never empty, never
+/// overlap and never touch
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: Vec::new() }
+    }
+
+    /// The disjoint intervals making up the set, in order
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of values in the set
+    pub fn len(&self) -> T {
+        self.iter().fold(T::zero(), |acc, i| acc + i.len())
+    }
+
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    /// Largest value in the set
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|i| i.end - T::one())
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end <= val);
+        self.intervals.get(idx).is_some_and(|i| i.contains(val))
+    }
+
+    /// Adds every value of `interval`, merging it with any intervals it
+    /// overlaps or touches
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let lo = self.intervals.partition_point(|i| i.end < interval.start);
+        let hi = self.intervals.partition_point(|i| i.start <= interval.end);
+        let mut merged = interval;
+        if lo < hi {
+            merged.start = merged.start.min(self.intervals[lo].start);
+            merged.end = merged.end.max(self.intervals[hi - 1].end);
+        }
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    /// Removes every value of `interval`
+    pub fn remove(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let lo = self.intervals.partition_point(|i| i.end <= interval.start);
+        let hi = self.intervals.partition_point(|i| i.start < interval.end);
+        let rest: Vec<_> = self.intervals[lo..hi]
+            .iter()
+            .flat_map(|i| i.difference(&interval))
+            .filter(|i| !i.is_empty())
+            .collect();
+        self.intervals.splice(lo..hi, rest);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.extend(other.iter());
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        // Both lists are sorted, so walk them together
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (self.intervals.iter().peekable(), other.intervals.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let both = x.intersect(y);
+            if !both.is_empty() {
+                intervals.push(both);
+            }
+            if x.end <= y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        for interval in other.iter() {
+            set.remove(interval);
+        }
+        set
+    }
+
+    /// Splits into the values below `at` and those from `at` onwards
+    pub fn split_at(&self, at: T) -> [Self; 2] {
+        let idx = self.intervals.partition_point(|i| i.end <= at);
+        let mut below = self.intervals[..idx].to_vec();
+        let mut above = Vec::with_capacity(self.intervals.len() - idx + 1);
+        if let Some(mid) = self.intervals.get(idx) {
+            let [lo, hi] = mid.split_at(at);
+            below.extend(Some(lo).filter(|i| !i.is_empty()));
+            above.extend(Some(hi).filter(|i| !i.is_empty()));
+            above.extend_from_slice(&self.intervals[idx + 1..]);
+        }
+        [Self { intervals: below }, Self { intervals: above }]
+    }
+}
+
+impl<T: PrimInt> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(interval);
+        set
+    }
+}
+
+impl<T: PrimInt> Extend<Interval<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = Interval<T>>>(&mut self, iter: I) {
+        for interval in iter {
+            self.insert(interval);
+        }
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// An `N` dimensional box, made of an interval along each axis
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cuboid<T, const N: usize> {
+    pub axes: [Interval<T>; N],
+}
+
+impl<T: PrimInt, const N: usize> Cuboid<T, N> {
+    pub const fn new(axes: [Interval<T>; N]) -> Self {
+        Self { axes }
+    }
+
+    /// Whether the box contains no points, which is true if it is empty
+    /// along any axis
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(Interval::is_empty)
+    }
+
+    /// Number of points in the box
+    pub fn volume(&self) -> u64 {
+        self.axes
+            .iter()
+            .map(|i| i.len().to_u64().expect("Interval length does not fit in u64"))
+            .product()
+    }
+
+    pub fn contains(&self, point: [T; N]) -> bool {
+        self.axes.iter().zip(point).all(|(i, p)| i.contains(p))
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::new(std::array::from_fn(|a| self.axes[a].intersect(&other.axes[a])))
+    }
+
+    /// Splits into the points below `at` along `axis` and those from `at`
+    /// onwards
+    pub fn split_at(&self, axis: usize, at: T) -> [Self; 2] {
+        let (mut below, mut above) = (*self, *self);
+        [below.axes[axis], above.axes[axis]] = self.axes[axis].split_at(at);
+        [below, above]
+    }
+}
+
+/// A piecewise-linear function, adding an offset to values in each of a
+/// list of sorted, disjoint source intervals and leaving other values as
+/// they are.
+///
+/// Offsets wrap, so that unsigned types can map values down, and mapped
+/// values are assumed to stay within the range of `T`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RangeMap<T> {
+    segments: Vec<(Interval<T>, T)>,
+}
+
+impl<T> RangeMap<T>
+where
+    T: PrimInt + WrappingAdd + WrappingSub + WrappingNeg,
+{
+    /// The identity map
+    pub fn new() -> Self {
+        Self { segments: Vec::new() }
+    }
+
+    /// The source intervals, in order, with the offset added to each
+    pub fn segments(&self) -> &[(Interval<T>, T)] {
+        &self.segments
+    }
+
+    /// Maps the values of `src` to those starting from `dst`, except any
+    /// already mapped by an earlier segment
+    pub fn insert(&mut self, src: Interval<T>, dst: T) {
+        let offset = dst.wrapping_sub(&src.start);
+        let unmapped: Vec<_> = self
+            .pieces(src)
+            .into_iter()
+            .filter(|(_, offset)| offset.is_none())
+            .map(|(piece, _)| (piece, offset))
+            .collect();
+        self.segments.extend(unmapped);
+        self.segments.sort_unstable_by_key(|(src, _)| src.start);
+    }
+
+    pub fn get(&self, val: T) -> T {
+        val.wrapping_add(&self.offset(val).unwrap_or_else(T::zero))
+    }
+
+    /// Offset of the segment containing `val`, if any
+    fn offset(&self, val: T) -> Option<T> {
+        let idx = self.segments.partition_point(|(src, _)| src.end <= val);
+        match self.segments.get(idx) {
+            Some(&(src, offset)) if src.contains(val) => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// Splits `interval` into the pieces covered by a single segment, with
+    /// its offset, or by none
+    fn pieces(&self, interval: Interval<T>) -> Vec<(Interval<T>, Option<T>)> {
+        let mut pieces = Vec::new();
+        let mut cur = interval.start;
+        let first = self.segments.partition_point(|(src, _)| src.end <= interval.start);
+        for &(src, offset) in self.segments[first..].iter().take_while(|(src, _)| src.start < interval.end) {
+            if cur < src.start {
+                pieces.push((Interval::new(cur, src.start), None));
+            }
+            pieces.push((src.intersect(&interval), Some(offset)));
+            cur = src.end;
+        }
+        if cur < interval.end {
+            pieces.push((Interval::new(cur, interval.end), None));
+        }
+        pieces
+    }
+
+    /// The map applying `self` and then `next`
+    pub fn then(&self, next: &Self) -> Self {
+        let mut composed = Self::new();
+        // No segment can contain the largest value, so both maps leave it
+        // as it is and it can be left out
+        let domain = Interval::new(T::min_value(), T::max_value());
+        for (piece, first) in self.pieces(domain) {
+            let offset = first.unwrap_or_else(T::zero);
+
+            // A piece may be mapped up to the largest value, which an
+            // exclusive end can't reach, so its last value is looked up on
+            // its own
+            let last = piece.end - T::one();
+            let image = Interval::new(piece.start, last).shift(offset);
+            let pieces = next
+                .pieces(image)
+                .into_iter()
+                .map(|(image, second)| (image.shift(offset.wrapping_neg()), second))
+                .chain([(Interval::new(last, piece.end), next.offset(last.wrapping_add(&offset)))]);
+
+            for (src, second) in pieces {
+                if first.is_none() && second.is_none() {
+                    continue;
+                }
+                let offset = offset.wrapping_add(&second.unwrap_or_else(T::zero));
+                match composed.segments.last_mut() {
+                    // Join pieces which ended up with the same offset
+                    Some((last, last_offset)) if last.end == src.start && *last_offset == offset => {
+                        last.end = src.end;
+                    }
+                    _ => composed.segments.push((src, offset)),
+                }
+            }
+        }
+        composed
+    }
+
+    /// The inverse map, if every value is mapped from exactly one value.
+    /// A map onto the largest value has no inverse, as no segment of the
+    /// inverse could contain it.
+    pub fn invert(&self) -> Option<Self> {
+        let mut segments: Vec<_> = self
+            .segments
+            .iter()
+            .map(|&(src, offset)| (src.shift(offset), offset.wrapping_neg()))
+            .collect();
+        segments.sort_unstable_by_key(|(dst, _)| dst.start);
+
+        // Mapped values must fit in a segment and not collide with each
+        // other or with unmapped ones
+        let fits = segments.iter().all(|(dst, _)| !dst.is_empty());
+        let disjoint = segments.windows(2).all(|w| w[0].0.end <= w[1].0.start);
+        let src: IntervalSet<T> = self.segments.iter().map(|&(src, _)| src).collect();
+        let dst: IntervalSet<T> = segments.iter().map(|&(dst, _)| dst).collect();
+        if !fits || !disjoint || dst != src {
+            return None;
+        }
+
+        Some(Self { segments })
+    }
+
+    /// The smallest value any of `input` maps to
+    pub fn min_over(&self, input: &IntervalSet<T>) -> Option<T> {
+        input
+            .iter()
+            .flat_map(|interval| self.pieces(interval))
+            .map(|(piece, offset)| piece.start.wrapping_add(&offset.unwrap_or_else(T::zero)))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Spans = &'static [(u32, u32)];
+
+    fn set<T: PrimInt>(spans: &[(T, T)]) -> IntervalSet<T> {
+        spans.iter().map(|&(s, e)| Interval::new(s, e)).collect()
+    }
+
+    fn spans(set: &IntervalSet<u32>) -> Vec<(u32, u32)> {
+        set.iter().map(|i| (i.start, i.end)).collect()
+    }
+
+    #[test]
+    fn interval_ops() {
+        let i = Interval::new(2u32, 6);
+        for (at, below, above) in [(0, (2, 2), (2, 6)), (4, (2, 4), (4, 6)), (9, (2, 6), (6, 6))] {
+            let [lo, hi] = i.split_at(at);
+            assert_eq!(((lo.start, lo.end), (hi.start, hi.end)), (below, above), "split at {at}");
+        }
+
+        let i = Interval::new(2u32, 8);
+        let cases = [
+            ((4, 6), (2, 4), (6, 8)),
+            ((0, 3), (2, 2), (3, 8)),
+            ((8, 10), (2, 8), (8, 8)),
+            ((5, 5), (2, 8), (8, 8)),
+        ];
+        for ((s, e), below, above) in cases {
+            let [lo, hi] = i.difference(&Interval::new(s, e));
+            assert_eq!(((lo.start, lo.end), (hi.start, hi.end)), (below, above), "minus {s}..{e}");
+        }
+
+        assert_eq!(i.intersect(&Interval::new(5, 20)), Interval::new(5, 8));
+        assert!(i.intersect(&Interval::new(8, 20)).is_empty());
+        assert!(i.overlaps(&Interval::new(7, 9)));
+        assert!(!i.overlaps(&Interval::new(8, 9)));
+        assert!(i.contains_interval(&Interval::new(2, 8)));
+        assert!(i.contains_interval(&Interval::new(30, 20)));
+        assert!(!i.contains_interval(&Interval::new(1, 3)));
+        assert_eq!(Interval::new(5u32, 3).len(), 0);
+    }
+
+    #[test]
+    fn normalization() {
+        let cases: &[(Spans, Spans)] = &[
+            (&[], &[]),
+            (&[(3, 3), (5, 4)], &[]),
+            (&[(1, 3), (5, 7)], &[(1, 3), (5, 7)]),
+            (&[(5, 7), (1, 3)], &[(1, 3), (5, 7)]),
+            (&[(1, 3), (3, 5)], &[(1, 5)]),
+            (&[(1, 4), (2, 6)], &[(1, 6)]),
+            (&[(1, 2), (4, 5), (2, 4)], &[(1, 5)]),
+            (&[(1, 2), (4, 5), (7, 8), (0, 10)], &[(0, 10)]),
+            (&[(1, 2), (6, 9), (4, 5), (8, 12)], &[(1, 2), (4, 5), (6, 12)]),
+        ];
+        for &(input, expected) in cases {
+            assert_eq!(spans(&set(input)), expected, "{input:?}");
+        }
+
+        let s = set(&[(1, 3), (5, 8)]);
+        assert_eq!((s.len(), s.min(), s.max()), (5, Some(1), Some(7)));
+        assert!(s.contains(2) && s.contains(5) && !s.contains(3) && !s.contains(8));
+        assert_eq!((set::<u32>(&[]).min(), set::<u32>(&[]).max()), (None, None));
+    }
+
+    #[test]
+    fn set_ops() {
+        let cases: &[(Spans, Spans, Spans, Spans, Spans)] = &[
+            // a, b, a | b, a & b, a - b
+            (&[(1, 5)], &[], &[(1, 5)], &[], &[(1, 5)]),
+            (&[], &[(1, 5)], &[(1, 5)], &[], &[]),
+            (&[(1, 3)], &[(3, 5)], &[(1, 5)], &[], &[(1, 3)]),
+            (&[(1, 4)], &[(2, 6)], &[(1, 6)], &[(2, 4)], &[(1, 2)]),
+            (&[(1, 2)], &[(5, 6)], &[(1, 2), (5, 6)], &[], &[(1, 2)]),
+            (&[(1, 5)], &[(2, 3)], &[(1, 5)], &[(2, 3)], &[(1, 2), (3, 5)]),
+            (&[(1, 3)], &[(0, 10)], &[(0, 10)], &[(1, 3)], &[]),
+            (&[(1, 3), (6, 9)], &[(2, 7)], &[(1, 9)], &[(2, 3), (6, 7)], &[(1, 2), (7, 9)]),
+            (
+                &[(0, 10)],
+                &[(1, 2), (4, 5), (9, 12)],
+                &[(0, 12)],
+                &[(1, 2), (4, 5), (9, 10)],
+                &[(0, 1), (2, 4), (5, 9)],
+            ),
+        ];
+        for &(a, b, union, inter, diff) in cases {
+            let (a_set, b_set) = (set(a), set(b));
+            assert_eq!(spans(&a_set.union(&b_set)), union, "{a:?} | {b:?}");
+            assert_eq!(spans(&a_set.intersection(&b_set)), inter, "{a:?} & {b:?}");
+            assert_eq!(spans(&b_set.intersection(&a_set)), inter, "{b:?} & {a:?}");
+            assert_eq!(spans(&a_set.difference(&b_set)), diff, "{a:?} - {b:?}");
+        }
+    }
+
+    #[test]
+    fn set_split_at() {
+        let s = set(&[(1, 3), (5, 8)]);
+        let cases: &[(u32, Spans, Spans)] = &[
+            (0, &[], &[(1, 3), (5, 8)]),
+            (1, &[], &[(1, 3), (5, 8)]),
+            (2, &[(1, 2)], &[(2, 3), (5, 8)]),
+            (3, &[(1, 3)], &[(5, 8)]),
+            (4, &[(1, 3)], &[(5, 8)]),
+            (6, &[(1, 3), (5, 6)], &[(6, 8)]),
+            (9, &[(1, 3), (5, 8)], &[]),
+        ];
+        for &(at, below, above) in cases {
+            let [lo, hi] = s.split_at(at);
+            assert_eq!((spans(&lo), spans(&hi)), (below.to_vec(), above.to_vec()), "split at {at}");
+        }
+
+        let [lo, hi] = set::<u32>(&[]).split_at(5);
+        assert!(lo.is_empty() && hi.is_empty());
+    }
+
+    #[test]
+    fn cuboids() {
+        let cube = Cuboid::new([Interval::new(0u32, 2), Interval::new(0, 3), Interval::new(1, 5)]);
+        assert_eq!(cube.volume(), 24);
+        assert!(cube.contains([1, 2, 4]) && !cube.contains([1, 3, 4]));
+
+        let cases = [(1, 1, 8, 16), (2, 3, 12, 12), (1, 0, 0, 24), (0, 7, 24, 0)];
+        for (axis, at, below, above) in cases {
+            let [lo, hi] = cube.split_at(axis, at);
+            assert_eq!((lo.volume(), hi.volume()), (below, above), "axis {axis} at {at}");
+            assert_eq!(lo.is_empty(), below == 0);
+        }
+
+        let flat = Cuboid::new([Interval::new(0u32, 2), Interval::new(4, 4), Interval::new(1, 5)]);
+        assert!(flat.is_empty());
+        assert_eq!(flat.volume(), 0);
+
+        let other = Cuboid::new([Interval::new(1u32, 9), Interval::new(2, 9), Interval::new(0, 2)]);
+        assert_eq!(cube.intersect(&other).volume(), 1);
+        assert!(cube.intersect(&flat).is_empty());
+    }
+
+    fn range_map(segments: &[(u8, u8, u8)]) -> RangeMap<u8> {
+        let mut map = RangeMap::new();
+        for &(start, end, dst) in segments {
+            map.insert(Interval::new(start, end), dst);
+        }
+        map
+    }
+
+    #[test]
+    fn compose() {
+        let maps = [
+            range_map(&[]),
+            // Swaps two blocks
+            range_map(&[(10, 20, 50), (50, 60, 10)]),
+            // Maps onto the largest value
+            range_map(&[(0, 6, 250), (15, 55, 100)]),
+            range_map(&[(250, 255, 0), (100, 120, 200), (90, 110, 5)]),
+        ];
+        for (i, first) in maps.iter().enumerate() {
+            for (j, second) in maps.iter().enumerate() {
+                let composed = first.then(second);
+                for val in 0..=u8::MAX {
+                    assert_eq!(composed.get(val), second.get(first.get(val)), "maps {i} then {j} at {val}");
+                }
+            }
+        }
+
+        // Pieces with the same offset are joined
+        let shift = range_map(&[(10, 20, 15)]);
+        assert_eq!(shift.then(&RangeMap::new()), shift);
+        let composed = shift.then(&range_map(&[(15, 25, 20)]));
+        assert_eq!(composed.segments(), [(Interval::new(10, 20), 10), (Interval::new(20, 25), 5)]);
+        assert_eq!(maps[2].then(&RangeMap::new()), maps[2]);
+    }
+
+    #[test]
+    fn invert() {
+        let swap = range_map(&[(10, 20, 50), (50, 60, 10)]);
+        let inv = swap.invert().unwrap();
+        for val in 0..=u8::MAX {
+            assert_eq!(inv.get(swap.get(val)), val);
+        }
+
+        let rotate = range_map(&[(0, 5, 10), (10, 15, 20), (20, 25, 0)]);
+        let inv = rotate.invert().unwrap();
+        assert_eq!((inv.get(10), inv.get(24), inv.get(3)), (0, 14, 23));
+        assert_eq!(RangeMap::<u8>::new().invert(), Some(RangeMap::new()));
+
+        // Onto values which are also mapped from themselves
+        assert_eq!(range_map(&[(10, 20, 15)]).invert(), None);
+        // Two blocks onto the same values
+        assert_eq!(range_map(&[(0, 10, 100), (20, 30, 105)]).invert(), None);
+        assert_eq!(range_map(&[(0, 10, 100), (100, 110, 0), (20, 30, 105)]).invert(), None);
+        // Onto the largest value
+        assert_eq!(range_map(&[(0, 6, 250), (250, 255, 0)]).invert(), None);
+    }
+
+    #[test]
+    fn min_over() {
+        let swap = range_map(&[(10, 20, 50), (50, 60, 10)]);
+        assert_eq!(swap.min_over(&set(&[(12, 15), (55, 58)])), Some(15));
+        assert_eq!(swap.min_over(&set(&[(0, 5)])), Some(0));
+        assert_eq!(swap.min_over(&set(&[(15, 40)])), Some(20));
+        assert_eq!(swap.min_over(&IntervalSet::new()), None);
+    }
+
+    #[test]
+    fn inclusive() {
+        assert_eq!(Interval::inclusive(2u8, 4), Some(Interval::new(2, 5)));
+        assert_eq!(Interval::inclusive(0u8, 254).map(|i| i.len()), Some(255));
+        assert_eq!(Interval::<u8>::inclusive(200, 255), None);
+        assert_eq!(Interval::<i64>::inclusive(0, i64::MAX), None);
+    }
+}
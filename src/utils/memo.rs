@@ -0,0 +1,159 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+/// Memo table for a recursive function, keyed by any hashable state.
+///
+/// Each table belongs to the caller rather than being global, so every run
+/// starts from a cold cache.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    map: HashMap<K, V>,
+    limit: Option<usize>,
+    /// Keys in the order they were stored, kept only for bounded tables
+    order: VecDeque<K>,
+    stats: MemoStats,
+}
+
+/// How often a [`Memo`] found a stored value
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of values dropped to make room in a bounded table
+    pub evictions: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            limit: None,
+            order: VecDeque::new(),
+            stats: MemoStats::default(),
+        }
+    }
+
+    /// A table holding at most `limit` values, which drops the oldest value
+    /// to make room for each new one once full
+    pub fn bounded(limit: usize) -> Self {
+        Self {
+            limit: Some(limit.max(1)),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the value stored for `key`, or computes and stores it with
+    /// `f`, which is given the table for any recursive calls
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(val) = self.map.get(&key) {
+            self.stats.hits += 1;
+            return val.clone();
+        }
+        self.stats.misses += 1;
+
+        let val = f(self);
+        if let Some(limit) = self.limit {
+            while self.map.len() >= limit {
+                let Some(oldest) = self.order.pop_front() else {
+                    break;
+                };
+                self.map.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+            self.order.push_back(key.clone());
+        }
+        self.map.insert(key, val.clone());
+        val
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Forgets every value and resets the statistics
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.hits + self.misses;
+        let rate = if total == 0 { 0.0 } else { self.hits as f64 / total as f64 * 100.0 };
+        write!(f, "{} hits, {} misses ({rate:.1}% hit rate)", self.hits, self.misses)?;
+        if self.evictions > 0 {
+            write!(f, ", {} evictions", self.evictions)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    #[test]
+    fn unbounded() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(90, &mut memo), 2_880_067_194_370_816_120);
+        assert_eq!(memo.len(), 89);
+        assert_eq!(memo.stats(), MemoStats { hits: 87, misses: 89, evictions: 0 });
+    }
+
+    #[test]
+    fn bounded() {
+        let mut memo = Memo::bounded(3);
+        for key in 0..5 {
+            memo.get_or_insert_with(key, |_| key * 10);
+        }
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.stats().evictions, 2);
+
+        // Only the oldest values were dropped
+        let mut calls = 0;
+        for key in [2, 3, 4] {
+            assert_eq!(memo.get_or_insert_with(key, |_| unreachable!()), key * 10);
+        }
+        memo.get_or_insert_with(0, |_| {
+            calls += 1;
+            0
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.stats(), MemoStats { hits: 3, misses: 6, evictions: 3 });
+
+        // Recursion still works with a table smaller than the call depth
+        let mut memo = Memo::bounded(4);
+        assert_eq!(fib(40, &mut memo), 102_334_155);
+        assert_eq!(memo.len(), 4);
+    }
+}
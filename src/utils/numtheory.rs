@@ -0,0 +1,232 @@
+use num::{rational::Ratio, Integer, PrimInt, Signed, Zero};
+use snafu::{ensure, OptionExt, Snafu};
+
+/// Signed primitive integers
+pub trait SignedInt: PrimInt + Integer + Signed {}
+impl<T: PrimInt + Integer + Signed> SignedInt for T {}
+
+/// The GCD of `a` and `b` with Bézout coefficients `x` and `y`, such that
+/// `a * x + b * y == gcd`, as `(gcd, x, y)`. The GCD is never negative.
+pub fn ext_gcd<T: SignedInt>(a: T, b: T) -> (T, T, T) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (T::one(), T::zero());
+    let (mut y0, mut y1) = (T::zero(), T::one());
+    while !r1.is_zero() {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0.is_negative() {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// The inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime
+pub fn mod_inverse<T: SignedInt>(a: T, m: T) -> Option<T> {
+    let (gcd, x, _) = ext_gcd(a, m);
+    gcd.is_one().then(|| x.mod_floor(&m))
+}
+
+/// The LCM of every number, which is 1 if there are none
+pub fn lcm_all<T: Integer + Copy>(nums: impl IntoIterator<Item = T>) -> T {
+    nums.into_iter().fold(T::one(), |acc, n| acc.lcm(&n))
+}
+
+/// The numbers `x` where `x ≡ residue (mod modulus)`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Congruence<T> {
+    pub residue: T,
+    pub modulus: T,
+}
+
+impl<T: SignedInt> Congruence<T> {
+    pub fn new(residue: T, modulus: T) -> Self {
+        Self { residue, modulus }
+    }
+
+    /// The smallest number in the congruence which is at least `min`
+    pub fn least_from(&self, min: T) -> T {
+        min + (self.residue - min).mod_floor(&self.modulus)
+    }
+}
+
+/// Combines congruences with the Chinese remainder theorem, allowing moduli
+/// which aren't coprime. Returns the congruence holding every solution,
+/// with its residue in `0..modulus`.
+pub fn crt<T: SignedInt>(congruences: impl IntoIterator<Item = Congruence<T>>) -> Result<Congruence<T>, CrtError> {
+    let mut acc = Congruence::new(T::zero(), T::one());
+    for Congruence { residue, modulus } in congruences {
+        ensure!(modulus.is_positive(), ModulusSnafu { modulus: wide(modulus) });
+
+        // Solve acc.residue + acc.modulus * k ≡ residue (mod modulus)
+        let (gcd, inv, _) = ext_gcd(acc.modulus, modulus);
+        let diff = residue.mod_floor(&modulus) - acc.residue;
+        ensure!(
+            (diff % gcd).is_zero(),
+            InconsistentSnafu { residue: wide(residue), modulus: wide(modulus) }
+        );
+        let step = modulus / gcd;
+        let k = mul_mod((diff / gcd).mod_floor(&step), inv.mod_floor(&step), step);
+
+        // As k < step, the new residue is below the new modulus
+        let lcm = acc.modulus.checked_mul(&step).context(TooLargeSnafu)?;
+        acc = Congruence::new(acc.residue + acc.modulus * k, lcm);
+    }
+    Ok(acc)
+}
+
+/// `a * b % m` for `a` and `b` in `0..m`, without overflowing even when the
+/// product doesn't fit in `T`
+fn mul_mod<T: SignedInt>(a: T, b: T, m: T) -> T {
+    if let Some(prod) = a.checked_mul(&b) {
+        return prod % m;
+    }
+
+    // Double and add, keeping every sum below m
+    let add = |x: T, y: T| if x >= m - y { x - (m - y) } else { x + y };
+    let (mut a, mut b, mut prod) = (a, b, T::zero());
+    while !b.is_zero() {
+        if b.is_odd() {
+            prod = add(prod, a);
+        }
+        a = add(a, a);
+        b = b >> 1;
+    }
+    prod
+}
+
+fn wide<T: SignedInt>(n: T) -> i128 {
+    n.to_i128().expect("Signed primitives fit in i128")
+}
+
+/// Solves `matrix * x == rhs` exactly, returning `None` if the matrix is
+/// singular
+pub fn solve_linear<T, const N: usize>(matrix: [[T; N]; N], rhs: [T; N]) -> Option<[Ratio<T>; N]>
+where
+    T: Integer + Clone,
+{
+    // Augmented rows, reduced to the identity by Gauss-Jordan elimination
+    let mut rows: Vec<Vec<Ratio<T>>> = matrix
+        .into_iter()
+        .zip(rhs)
+        .map(|(row, b)| row.into_iter().chain([b]).map(Ratio::from_integer).collect())
+        .collect();
+
+    for col in 0..N {
+        let pivot = (col..N).find(|&r| !rows[r][col].is_zero())?;
+        rows.swap(col, pivot);
+        let pivot = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r == col || row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col].clone() / pivot[col].clone();
+            for (val, p) in row.iter_mut().zip(&pivot) {
+                *val = val.clone() - factor.clone() * p.clone();
+            }
+        }
+    }
+
+    Some(std::array::from_fn(|i| rows[i][N].clone() / rows[i][i].clone()))
+}
+
+#[derive(Debug, Snafu)]
+pub enum CrtError {
+    #[snafu(display("Modulus {modulus} is not positive"))]
+    Modulus { modulus: i128 },
+    #[snafu(display("x ≡ {residue} (mod {modulus}) contradicts earlier congruences"))]
+    Inconsistent { residue: i128, modulus: i128 },
+    #[snafu(display("Combined modulus is too large"))]
+    TooLarge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_inverse() {
+        assert_eq!(ext_gcd(240i64, 46), (2, -9, 47));
+        assert_eq!(ext_gcd(-12i32, 18), (6, 1, 1));
+        assert_eq!(ext_gcd(0i32, -5), (5, 0, -1));
+        assert_eq!(ext_gcd(7i32, 0), (7, 1, 0));
+
+        assert_eq!(mod_inverse(3i64, 11), Some(4));
+        assert_eq!(mod_inverse(-3i64, 11), Some(7));
+        assert_eq!(mod_inverse(4i64, 8), None);
+        assert_eq!(mod_inverse(6i64, 9), None);
+        assert_eq!(lcm_all([4u64, 6, 10]), 60);
+        assert_eq!(lcm_all::<u64>([]), 1);
+    }
+
+    #[test]
+    fn crt_coprime() {
+        let c = [Congruence::new(2i64, 3), Congruence::new(3, 5), Congruence::new(2, 7)];
+        let c = crt(c).unwrap();
+        assert_eq!(c, Congruence::new(23, 105));
+        assert_eq!(c.least_from(30), 128);
+        assert_eq!(crt::<i64>([]).unwrap(), Congruence::new(0, 1));
+
+        // Residues are reduced first
+        let c = crt([Congruence::new(-1i64, 4), Congruence::new(17, 5)]).unwrap();
+        assert_eq!(c, Congruence::new(7, 20));
+    }
+
+    #[test]
+    fn crt_not_coprime() {
+        let c = crt([Congruence::new(2i64, 4), Congruence::new(4, 6)]).unwrap();
+        assert_eq!(c, Congruence::new(10, 12));
+        let c = [Congruence::new(3i64, 4), Congruence::new(5, 6), Congruence::new(2, 9)];
+        let c = crt(c).unwrap();
+        assert_eq!(c, Congruence::new(11, 36));
+        let c = crt([Congruence::new(5i64, 10), Congruence::new(5, 10)]).unwrap();
+        assert_eq!(c, Congruence::new(5, 10));
+    }
+
+    #[test]
+    fn crt_errors() {
+        let err = crt([Congruence::new(1i64, 4), Congruence::new(2, 6)]).unwrap_err();
+        assert!(matches!(err, CrtError::Inconsistent { residue: 2, modulus: 6 }));
+        assert!(matches!(crt([Congruence::new(1i64, 0)]), Err(CrtError::Modulus { modulus: 0 })));
+        assert!(matches!(crt([Congruence::new(1i64, -3)]), Err(CrtError::Modulus { modulus: -3 })));
+
+        let big = i64::MAX / 2;
+        let c = crt([Congruence::new(0i64, big), Congruence::new(0, big - 2)]);
+        assert!(matches!(c, Err(CrtError::TooLarge)));
+    }
+
+    #[test]
+    fn crt_large_moduli() {
+        // Moduli well above the square root of i64::MAX, whose product of
+        // residues would overflow
+        let (m1, m2) = (3_000_000_019i64, 3_000_000_037);
+        let x = 8_000_000_000_000_000_123i128 % (i128::from(m1) * i128::from(m2));
+        let congruence = |m: i64| Congruence::new((x % i128::from(m)) as i64, m);
+        let c = crt([congruence(m1), congruence(m2)]).unwrap();
+        assert_eq!(i128::from(c.residue), x);
+        assert_eq!(c.modulus, m1 * m2);
+
+        assert_eq!(mul_mod(i64::MAX - 1, i64::MAX - 2, i64::MAX), 2);
+    }
+
+    #[test]
+    fn linear() {
+        let s = solve_linear([[2i64, 1], [1, 3]], [3, 5]).unwrap();
+        assert_eq!(s, [Ratio::new(4, 5), Ratio::new(7, 5)]);
+        let s = solve_linear([[0i64, 1], [1, 0]], [3, 5]).unwrap();
+        assert_eq!(s, [Ratio::from_integer(5), Ratio::from_integer(3)]);
+        assert!(solve_linear([[1i64, 2], [2, 4]], [1, 2]).is_none());
+        assert!(solve_linear([[0i64, 0], [0, 0]], [0, 0]).is_none());
+
+        // The first claw machine from 2024 day 13: A moves X+94, Y+34, B
+        // moves X+22, Y+67, and the prize is at X=8400, Y=5400
+        let s = solve_linear([[94i64, 22], [34, 67]], [8400, 5400]).unwrap();
+        assert_eq!(s, [Ratio::from_integer(80), Ratio::from_integer(40)]);
+        // The second has no whole solution
+        let s = solve_linear([[26i64, 67], [66, 21]], [12748, 12176]).unwrap();
+        assert!(!s[0].is_integer() || !s[1].is_integer());
+    }
+}
@@ -0,0 +1,230 @@
+use itertools::Itertools;
+use snafu::{ensure, OptionExt, Snafu};
+
+use super::view::GridView;
+
+/// The 6-row font used by most letter-drawing puzzles, with only the
+/// letters known from real puzzle output. Letters take 4 columns plus a
+/// gap, except for Y which is drawn 5 columns wide and runs into the next
+/// letter.
+#[rustfmt::skip]
+const SMALL: Font<6> = Font {
+    cell: 5,
+    glyphs: &[
+        ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The 10-row font used by the larger letter-drawing puzzles, with only
+/// the letters known from real puzzle output, 8 columns apart
+#[rustfmt::skip]
+const LARGE: Font<10> = Font {
+    cell: 8,
+    glyphs: &[
+        ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+        ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+        ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+        ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+        ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+        ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+        ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+        ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+        ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+        ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+        ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+    ],
+};
+
+/// A fixed-height font, with each glyph drawn as rows of `#` and `.`
+struct Font<const H: usize> {
+    /// Columns from the start of one letter to the next
+    cell: usize,
+    glyphs: &'static [(char, [&'static str; H])],
+}
+
+impl<const H: usize> Font<H> {
+    /// Each glyph as lit row bitmasks per column
+    fn columns(&self) -> Glyphs {
+        Glyphs {
+            cell: self.cell,
+            glyphs: self.glyphs.iter().map(|(c, rows)| (*c, glyph_columns(rows))).collect(),
+        }
+    }
+}
+
+/// A font converted to column bitmasks for matching
+struct Glyphs {
+    cell: usize,
+    glyphs: Vec<(char, Vec<u16>)>,
+}
+
+/// Reads the letters drawn by the lit cells of a `width` by `height` area.
+/// Letters missing from the fonts are reported as unknown.
+pub fn read<F>(width: usize, height: usize, lit: F) -> Result<String, OcrError>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    // Find the rows containing the text
+    let rows = (0..height).filter(|&y| (0..width).any(|x| lit(x, y)));
+    let (top, bottom) = rows.minmax().into_option().context(EmptySnafu)?;
+    let text_height = bottom - top + 1;
+
+    // Each column of the text as a bitmask of lit rows
+    let columns: Vec<u16> = (0..width)
+        .map(|x| (0..text_height).fold(0, |m, y| m | (u16::from(lit(x, top + y)) << y)))
+        .collect();
+
+    let font = match text_height {
+        6 => SMALL.columns(),
+        10 => LARGE.columns(),
+        _ => return HeightSnafu { height: text_height }.fail(),
+    };
+
+    // Letters are usually separated by empty columns, but a glyph filling
+    // its whole cell runs straight into the next one. Take the widest glyph
+    // matching at each letter which ends at a gap or fills its cell.
+    let mut text = String::new();
+    let mut pos = 0;
+    while pos < columns.len() {
+        if columns[pos] == 0 {
+            pos += 1;
+            continue;
+        }
+
+        let rest = &columns[pos..];
+        let (c, len) = font
+            .glyphs
+            .iter()
+            .filter(|(_, g)| {
+                let ends = rest.get(g.len()).copied().unwrap_or(0) == 0;
+                rest.starts_with(g) && (ends || g.len() >= font.cell)
+            })
+            .max_by_key(|(_, g)| g.len())
+            .map(|(c, g)| (*c, g.len()))
+            .context(UnknownSnafu { index: text.len() })?;
+        text.push(c);
+        pos += len;
+    }
+
+    ensure!(!text.is_empty(), EmptySnafu);
+    Ok(text)
+}
+
+/// Reads the letters drawn by the `true` cells of a grid
+pub fn read_grid<G: GridView<Item = bool>>(grid: &G) -> Result<String, OcrError> {
+    let width = grid.width();
+    read(width, grid.height(), |x, y| grid.get(y * width + x))
+}
+
+/// Converts a glyph to lit row bitmasks per column, without empty
+/// columns at the edges
+fn glyph_columns(rows: &[&str]) -> Vec<u16> {
+    let width = rows[0].len();
+    let mut columns: Vec<u16> = (0..width)
+        .map(|x| {
+            rows.iter()
+                .enumerate()
+                .fold(0, |m, (y, r)| m | (u16::from(r.as_bytes()[x] == b'#') << y))
+        })
+        .collect();
+    while columns.last() == Some(&0) {
+        columns.pop();
+    }
+    let lead = columns.iter().take_while(|&&c| c == 0).count();
+    columns.drain(..lead);
+    columns
+}
+
+#[derive(Debug, Snafu)]
+pub enum OcrError {
+    #[snafu(display("No letters found"))]
+    Empty,
+    #[snafu(display("Text height of {height} matches no known font"))]
+    Height { height: usize },
+    #[snafu(display("Unrecognized letter at position {index}"))]
+    Unknown { index: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_rows(rows: &[&str]) -> Result<String, OcrError> {
+        read(rows[0].len(), rows.len(), |x, y| rows[y].as_bytes()[x] == b'#')
+    }
+
+    #[test]
+    fn small() {
+        // The 5 column Y runs into the S
+        let rows = [
+            ".##..###...##..#...#.###..##...##..#....####.",
+            "#..#.#..#.#..#.#...##....#..#.#..#.#....#....",
+            "#....#..#.#..#..#.#.#....#....#..#.#....###..",
+            "#.##.###..####...#...##..#....####.#....#....",
+            "#..#.#.#..#..#...#.....#.#..#.#..#.#....#....",
+            ".###.#..#.#..#...#..###...##..#..#.####.####.",
+        ];
+        assert_eq!(read_rows(&rows).unwrap(), "GRAYSCALE");
+    }
+
+    #[test]
+    fn large() {
+        let rows = [
+            "#....#....##....######..######..#.......",
+            "#....#...#..#........#..#.......#.......",
+            "#....#..#....#.......#..#.......#.......",
+            "#....#..#....#......#...#.......#.......",
+            "######..#....#.....#....#####...#.......",
+            "#....#..######....#.....#.......#.......",
+            "#....#..#....#...#......#.......#.......",
+            "#....#..#....#..#.......#.......#.......",
+            "#....#..#....#..#.......#.......#.......",
+            "#....#..#....#..######..######..######..",
+        ];
+        assert_eq!(read_rows(&rows).unwrap(), "HAZEL");
+    }
+
+    #[test]
+    fn every_glyph() {
+        fn check<const H: usize>(font: &Font<H>) {
+            for (c, rows) in font.glyphs {
+                assert_eq!(read_rows(rows).unwrap(), c.to_string());
+            }
+        }
+        check(&SMALL);
+        check(&LARGE);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(read_rows(&["....", "...."]), Err(OcrError::Empty)));
+        assert!(matches!(read_rows(&["#", "#", "#"]), Err(OcrError::Height { height: 3 })));
+        let rows = ["####", "####", "####", "####", "####", "####"];
+        assert!(matches!(read_rows(&rows), Err(OcrError::Unknown { index: 0 })));
+
+        // No puzzle has drawn a T, so it isn't in the font
+        let rows = ["###.", ".#..", ".#..", ".#..", ".#..", ".#.."];
+        assert!(matches!(read_rows(&rows), Err(OcrError::Unknown { index: 0 })));
+    }
+}
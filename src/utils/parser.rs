@@ -1,8 +1,12 @@
-use std::{ops::{Range, RangeFrom, RangeTo}, mem::MaybeUninit};
+use std::{collections::HashMap, hash::Hash, marker::PhantomData, ops::{Range, RangeFrom, RangeTo}, mem::MaybeUninit};
 
     use nom::{
-        branch::alt, character::complete::line_ending, combinator::{eof, recognize}, error::ParseError, sequence::terminated, Compare, IResult, InputIter, InputLength, InputTake, Offset, Parser, Slice
+        branch::alt, bytes::complete::tag, character::complete::{alphanumeric1, char, line_ending, space0, space1}, combinator::{eof, map_opt, recognize}, error::ParseError, sequence::{delimited, pair, preceded, separated_pair, terminated}, Compare, Finish, IResult, InputIter, InputLength, InputTake, Offset, Parser, Slice
     };
+    use smallvec::{Array, SmallVec};
+    use snafu::{ensure, Snafu};
+
+    use super::{Coord, NomFail};
 
     /// Matches the given parser followed by either eof or a line ending.
     pub fn line<I, O, E, F>(parser: F) -> impl FnMut(I) -> IResult<I, O, E>
@@ -57,4 +61,587 @@ use std::{ops::{Range, RangeFrom, RangeTo}, mem::MaybeUninit};
 
             Err(nom::Err::Error(E::from_error_kind(input, nom::error::ErrorKind::Eof)))
         }
-    }
\ No newline at end of file
+    }
+
+    /// Runs `parser` until it fails, passing each result to `f`.
+    pub fn for_each<I, O, E, F, G>(mut parser: F, mut f: G) -> impl FnMut(I) -> IResult<I, (), E>
+    where
+        I: Clone + InputLength,
+        E: ParseError<I>,
+        F: Parser<I, O, E>,
+        G: FnMut(O),
+    {
+        move |mut input: I| loop {
+            let len = input.input_len();
+            match parser.parse(input.clone()) {
+                Ok((rest, val)) => {
+                    // Stop rather than loop forever on a parser that takes nothing
+                    if rest.input_len() == len {
+                        return Err(nom::Err::Error(E::from_error_kind(input, nom::error::ErrorKind::Many0)));
+                    }
+                    f(val);
+                    input = rest;
+                }
+                Err(nom::Err::Error(_)) => return Ok((input, ())),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Integer types which can be parsed in decimal, with a leading `-` for signed types.
+    pub trait NomInt: Sized {
+        fn parse_int<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E>;
+    }
+
+    macro_rules! nom_int {
+        ($($ty:ident)*) => {$(
+            impl NomInt for $ty {
+                fn parse_int<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+                    nom::character::complete::$ty(input)
+                }
+            }
+        )*};
+    }
+
+    nom_int!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+    impl NomInt for usize {
+        fn parse_int<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+            map_opt(u64::parse_int, |n| n.try_into().ok())(input)
+        }
+    }
+
+    impl NomInt for isize {
+        fn parse_int<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+            map_opt(i64::parse_int, |n| n.try_into().ok())(input)
+        }
+    }
+
+    /// Parses a decimal integer of any width, e.g. `int::<i16>`.
+    pub fn int<'a, T: NomInt, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], T, E> {
+        T::parse_int(input)
+    }
+
+    /// Parses a coordinate written as `x,y`.
+    pub fn coord<'a, T: NomInt, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Coord<T>, E> {
+        separated_pair(int, char(','), int)
+            .map(|(x, y)| Coord { x, y })
+            .parse(input)
+    }
+
+    /// Parses a coordinate written as `x=.., y=..`, with the space optional.
+    pub fn xy_coord<'a, T: NomInt, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Coord<T>, E> {
+        separated_pair(
+            preceded(tag("x="), int),
+            pair(char(','), space0),
+            preceded(tag("y="), int),
+        )
+        .map(|(x, y)| Coord { x, y })
+        .parse(input)
+    }
+
+    /// Runs `parser` at least once with `sep` between, collecting the results into a `SmallVec`.
+    pub fn separated_smallvec<A, I, O, O2, E, F, G>(mut sep: G, mut parser: F) -> impl FnMut(I) -> IResult<I, SmallVec<A>, E>
+    where
+        A: Array<Item = O>,
+        I: Clone,
+        E: ParseError<I>,
+        F: Parser<I, O, E>,
+        G: Parser<I, O2, E>,
+    {
+        move |input: I| {
+            let (mut input, first) = parser.parse(input)?;
+            let mut vec = SmallVec::new();
+            vec.push(first);
+            loop {
+                let Ok((rest, _)) = sep.parse(input.clone()) else {
+                    return Ok((input, vec));
+                };
+                match parser.parse(rest) {
+                    Ok((rest, val)) => {
+                        vec.push(val);
+                        input = rest;
+                    }
+                    Err(nom::Err::Error(_)) => return Ok((input, vec)),
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+    }
+
+    /// Parses a list like `1, 2,3`, with optional spaces after each comma.
+    pub fn comma_list<'a, A, O, E, F>(parser: F) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], SmallVec<A>, E>
+    where
+        A: Array<Item = O>,
+        E: ParseError<&'a [u8]>,
+        F: Parser<&'a [u8], O, E>,
+    {
+        separated_smallvec(pair(char(','), space0), parser)
+    }
+
+    /// Parses a list like `1 2  3`, separated by spaces or tabs.
+    pub fn space_list<'a, A, O, E, F>(parser: F) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], SmallVec<A>, E>
+    where
+        A: Array<Item = O>,
+        E: ParseError<&'a [u8]>,
+        F: Parser<&'a [u8], O, E>,
+    {
+        separated_smallvec(space1, parser)
+    }
+
+    /// A node label with the labels of its left and right neighbors.
+    pub type Adjacency<O> = (O, (O, O));
+
+    /// Parses an adjacency line like `AAA = (BBB, CCC)`, using `label` for each of the names.
+    /// Spaces after the closing parenthesis are ignored.
+    pub fn adjacency<'a, O, E, F>(mut label: F) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Adjacency<O>, E>
+    where
+        E: ParseError<&'a [u8]>,
+        F: Parser<&'a [u8], O, E>,
+    {
+        move |input: &'a [u8]| {
+            let (input, node) = label.parse(input)?;
+            let (input, _) = delimited(space0, char('='), space0)(input)?;
+            let (input, _) = char('(')(input)?;
+            let (input, left) = label.parse(input)?;
+            let (input, _) = pair(char(','), space0)(input)?;
+            let (input, right) = label.parse(input)?;
+            let (input, _) = line(pair(char(')'), space0))(input)?;
+            Ok((input, (node, (left, right))))
+        }
+    }
+
+    /// Matches an empty line, including its line ending.
+    pub fn blank_line<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+        recognize(pair(space0, line_ending))(input)
+    }
+
+    /// Splits the input into blocks of lines separated by blank lines. Each block keeps its
+    /// final line ending.
+    pub fn paragraphs(input: &[u8]) -> Paragraphs<'_> {
+        Paragraphs { input }
+    }
+
+    /// Iterator over blank-line-separated blocks, created by [`paragraphs`]
+    #[derive(Debug, Clone)]
+    pub struct Paragraphs<'a> {
+        input: &'a [u8],
+    }
+
+    impl<'a> Iterator for Paragraphs<'a> {
+        type Item = &'a [u8];
+
+        fn next(&mut self) -> Option<&'a [u8]> {
+            // Skip leading blank lines
+            while let Ok((rest, _)) = blank_line::<()>(self.input) {
+                self.input = rest;
+            }
+            if self.input.is_empty() {
+                return None;
+            }
+
+            let mut end = 0;
+            while end < self.input.len() {
+                let eol = self.input[end..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(self.input.len(), |p| end + p + 1);
+                end = eol;
+                if blank_line::<()>(&self.input[end..]).is_ok() {
+                    break;
+                }
+            }
+
+            let (block, rest) = self.input.split_at(end);
+            self.input = rest;
+            Some(block)
+        }
+    }
+
+    /// Parses every line of the input with `parser`, failing with the error from the first
+    /// line it can't parse.
+    pub fn parse_lines<'a, O, F>(mut input: &'a [u8], parser: F) -> Result<Vec<O>, NomFail>
+    where
+        F: Parser<&'a [u8], O, nom::error::Error<&'a [u8]>>,
+    {
+        let mut parser = line(parser);
+        let mut vec = Vec::new();
+        // Each line consumes at least its line ending, so this always makes progress
+        while !input.is_empty() {
+            let (rest, val) = parser.parse(input).finish()?;
+            vec.push(val);
+            input = rest;
+        }
+        Ok(vec)
+    }
+
+    /// A value which can fill a `{}` in a [`format_parser!`] format string.
+    pub trait FormatField<'a>: Sized {
+        fn parse_field<E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E>;
+    }
+
+    impl<'a, T: NomInt> FormatField<'a> for T {
+        fn parse_field<E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+            T::parse_int(input)
+        }
+    }
+
+    /// A label made of letters and digits
+    impl<'a> FormatField<'a> for &'a [u8] {
+        fn parse_field<E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+            alphanumeric1(input)
+        }
+    }
+
+    /// A space-separated list of integers
+    impl<'a, A> FormatField<'a> for SmallVec<A>
+    where
+        A: Array,
+        A::Item: NomInt,
+    {
+        fn parse_field<E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+            space_list(int)(input)
+        }
+    }
+
+    /// A tuple of [`FormatField`]s, filling the `{}`s of a format string in order.
+    pub trait FormatFields<'a>: Sized {
+        /// Number of fields, which must match the number of `{}`s
+        const FIELDS: usize;
+
+        fn parse_fields<E: ParseError<&'a [u8]>>(pieces: &[&str], input: &'a [u8]) -> IResult<&'a [u8], Self, E>;
+    }
+
+    macro_rules! format_fields {
+        ($($name:ident)+) => {
+            impl<'a, $($name: FormatField<'a>),+> FormatFields<'a> for ($($name,)+) {
+                const FIELDS: usize = [$(stringify!($name)),+].len();
+
+                #[allow(non_snake_case)]
+                fn parse_fields<E: ParseError<&'a [u8]>>(pieces: &[&str], input: &'a [u8]) -> IResult<&'a [u8], Self, E> {
+                    let mut pieces = pieces.iter();
+                    $(
+                        let (input, _) = tag(*pieces.next().unwrap())(input)?;
+                        let (input, $name) = $name::parse_field(input)?;
+                    )+
+                    let (input, _) = tag(*pieces.next().unwrap())(input)?;
+                    Ok((input, ($($name,)+)))
+                }
+            }
+        };
+    }
+
+    format_fields!(A);
+    format_fields!(A B);
+    format_fields!(A B C);
+    format_fields!(A B C D);
+    format_fields!(A B C D E2);
+    format_fields!(A B C D E2 F);
+    format_fields!(A B C D E2 F G);
+    format_fields!(A B C D E2 F G H);
+
+    /// Number of `{}` placeholders in a format string.
+    pub const fn placeholders(fmt: &str) -> usize {
+        let bytes = fmt.as_bytes();
+        let mut count = 0;
+        let mut i = 0;
+        while i + 1 < bytes.len() {
+            if bytes[i] == b'{' && bytes[i + 1] == b'}' {
+                count += 1;
+                i += 1;
+            }
+            i += 1;
+        }
+        count
+    }
+
+    /// Parser built by [`format_parser!`], producing the tuple `T` with errors of type `E`
+    #[derive(Debug, Clone)]
+    pub struct FormatParser<T, E> {
+        pieces: Vec<&'static str>,
+        output: PhantomData<fn() -> (T, E)>,
+    }
+
+    impl<T, E> FormatParser<T, E> {
+        /// Parser for `fmt`, which must have a `{}` for each field of `T`.
+        pub fn new<'a>(fmt: &'static str) -> Result<Self, FormatError>
+        where
+            T: FormatFields<'a>,
+        {
+            let pieces: Vec<_> = fmt.split("{}").collect();
+            ensure!(
+                pieces.len() == T::FIELDS + 1,
+                FormatSnafu { fields: T::FIELDS, placeholders: pieces.len() - 1 }
+            );
+            Ok(Self { pieces, output: PhantomData })
+        }
+    }
+
+    /// A format string whose placeholders don't match the fields to parse
+    #[derive(Debug, Snafu)]
+    #[snafu(display("Format string has {placeholders} placeholders for {fields} fields"))]
+    pub struct FormatError {
+        fields: usize,
+        placeholders: usize,
+    }
+
+    impl<'a, T, E> Parser<&'a [u8], T, E> for FormatParser<T, E>
+    where
+        T: FormatFields<'a>,
+        E: ParseError<&'a [u8]>,
+    {
+        fn parse(&mut self, input: &'a [u8]) -> IResult<&'a [u8], T, E> {
+            T::parse_fields(&self.pieces, input)
+        }
+    }
+
+    /// Assigns dense ids, in order of first appearance, to labels borrowed from the input.
+    ///
+    /// Graph-style days can match node names with [`interned`] and store only the ids, so no
+    /// name is copied while parsing. Keep the input alive alongside the interner to look the
+    /// names back up with [`Interner::label`].
+    #[derive(Debug)]
+    pub struct Interner<'a, K: ?Sized = [u8]> {
+        ids: HashMap<&'a K, usize>,
+        labels: Vec<&'a K>,
+    }
+
+    impl<K: ?Sized> Clone for Interner<'_, K> {
+        fn clone(&self) -> Self {
+            Self {
+                ids: self.ids.clone(),
+                labels: self.labels.clone(),
+            }
+        }
+    }
+
+    impl<K: ?Sized> Default for Interner<'_, K> {
+        fn default() -> Self {
+            Self {
+                ids: HashMap::new(),
+                labels: Vec::new(),
+            }
+        }
+    }
+
+    impl<'a, K: ?Sized + Hash + Eq> Interner<'a, K> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Id of `label`, assigning the next one if it is new.
+        pub fn intern(&mut self, label: &'a K) -> usize {
+            *self.ids.entry(label).or_insert_with(|| {
+                self.labels.push(label);
+                self.labels.len() - 1
+            })
+        }
+
+        /// Id of `label`, if it has been interned.
+        pub fn id(&self, label: &K) -> Option<usize> {
+            self.ids.get(label).copied()
+        }
+
+        /// Label with the given id.
+        pub fn label(&self, id: usize) -> &'a K {
+            self.labels[id]
+        }
+
+        /// All labels, indexed by id.
+        pub fn labels(&self) -> &[&'a K] {
+            &self.labels
+        }
+
+        /// Number of distinct labels.
+        pub fn len(&self) -> usize {
+            self.labels.len()
+        }
+
+        /// Whether no labels have been interned.
+        pub fn is_empty(&self) -> bool {
+            self.labels.is_empty()
+        }
+    }
+
+    /// Matches a label with `parser`, returning its id in `symbols`.
+    pub fn interned<'s, 'a: 's, E, F>(symbols: &'s mut Interner<'a>, mut parser: F) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], usize, E> + 's
+    where
+        E: ParseError<&'a [u8]>,
+        F: Parser<&'a [u8], &'a [u8], E> + 's,
+    {
+        move |input: &'a [u8]| {
+            let (input, label) = parser.parse(input)?;
+            Ok((input, symbols.intern(label)))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        type Res<'a, T> = IResult<&'a [u8], T>;
+
+        #[test]
+        fn int_bounds() {
+            assert_eq!(int::<u8, ()>(b"255,").unwrap(), (&b","[..], 255));
+            assert!(int::<u8, ()>(b"256").is_err());
+            assert!(int::<u8, ()>(b"-1").is_err());
+            assert_eq!(int::<i8, ()>(b"-128").unwrap().1, -128);
+            assert!(int::<i8, ()>(b"128").is_err());
+            assert!(int::<i8, ()>(b"-129").is_err());
+            assert_eq!(int::<usize, ()>(b"18446744073709551615").unwrap().1, usize::MAX);
+            assert!(int::<usize, ()>(b"18446744073709551616").is_err());
+            assert_eq!(int::<isize, ()>(b"-42x").unwrap(), (&b"x"[..], -42));
+        }
+
+        #[test]
+        fn coords() {
+            let r: Res<'_, Coord<i32>> = coord(b"3,-4 ");
+            assert_eq!(r.unwrap(), (&b" "[..], Coord { x: 3, y: -4 }));
+            let r: Res<'_, Coord<u8>> = coord(b"3, 4");
+            assert!(r.is_err());
+
+            let r: Res<'_, Coord<i64>> = xy_coord(b"x=-2, y=15");
+            assert_eq!(r.unwrap().1, Coord { x: -2, y: 15 });
+            let r: Res<'_, Coord<i64>> = xy_coord(b"x=7,y=0");
+            assert_eq!(r.unwrap().1, Coord { x: 7, y: 0 });
+        }
+
+        #[test]
+        fn lists() {
+            let r: Res<'_, SmallVec<[u8; 4]>> = comma_list(int)(b"1, 2,3");
+            assert_eq!(r.unwrap().1.as_slice(), [1, 2, 3]);
+
+            // A trailing separator is left unconsumed
+            let r: Res<'_, SmallVec<[u8; 4]>> = comma_list(int)(b"1,2,");
+            assert_eq!(r.unwrap(), (&b","[..], SmallVec::from_slice(&[1, 2])));
+            let r: Res<'_, SmallVec<[u8; 4]>> = space_list(int)(b"1  2 \n");
+            assert_eq!(r.unwrap(), (&b" \n"[..], SmallVec::from_slice(&[1, 2])));
+
+            // Lists need at least one item
+            let r: Res<'_, SmallVec<[u8; 4]>> = comma_list(int)(b"");
+            assert!(r.is_err());
+            let r: Res<'_, SmallVec<[u8; 4]>> = space_list(int)(b" 1");
+            assert!(r.is_err());
+        }
+
+        #[test]
+        fn paragraph_blocks() {
+            let blocks: Vec<_> = paragraphs(b"ab\r\ncd\r\n\r\nef\r\n\r\n").collect();
+            assert_eq!(blocks, [&b"ab\r\ncd\r\n"[..], b"ef\r\n"]);
+
+            let blocks: Vec<_> = paragraphs(b"\n\nab\n  \ncd").collect();
+            assert_eq!(blocks, [&b"ab\n"[..], b"cd"]);
+
+            assert_eq!(paragraphs(b"\n\n").count(), 0);
+        }
+
+        #[test]
+        fn lines() {
+            assert_eq!(parse_lines(b"1\n2\n", int::<u8, _>).unwrap(), [1, 2]);
+            assert_eq!(parse_lines(b"1\r\n2", int::<u8, _>).unwrap(), [1, 2]);
+
+            assert_eq!(parse_lines(b"", int::<u8, _>).unwrap(), []);
+
+            // The failing line's own error is reported
+            let err = parse_lines(b"1\n300\n", int::<u8, _>).unwrap_err();
+            assert_eq!(err.to_string(), r#"Parse error (Digit): "300\n""#);
+            let err = parse_lines(b"1\n2x\n3\n", int::<u8, _>).unwrap_err();
+            assert_eq!(err.to_string(), r#"Parse error (CrLf): "x\n3\n""#);
+        }
+
+        #[test]
+        fn repeat() {
+            let mut vals = Vec::new();
+            let r: Res<'_, ()> = for_each(line(int::<u8, _>), |v| vals.push(v))(b"1\n2\nx\n");
+            assert_eq!(r.unwrap().0, b"x\n");
+            assert_eq!(vals, [1, 2]);
+
+            // Nothing matching is not an error
+            let r: Res<'_, ()> = for_each(int::<u8, _>, |_| panic!())(b"x");
+            assert_eq!(r.unwrap().0, b"x");
+
+            // A parser consuming nothing would never stop
+            let r: Res<'_, ()> = for_each(space0, |_| ())(b"x");
+            assert!(matches!(r, Err(nom::Err::Error(e)) if e.code == nom::error::ErrorKind::Many0));
+
+            // Failures aren't recovered from
+            let r: Res<'_, ()> = for_each(nom::combinator::cut(char('a')), |_| ())(b"aab");
+            assert!(matches!(r, Err(nom::Err::Failure(e)) if e.input == b"b"));
+        }
+
+        #[test]
+        fn blank_lines() {
+            assert_eq!(blank_line::<()>(b"\nab").unwrap(), (&b"ab"[..], &b"\n"[..]));
+            assert_eq!(blank_line::<()>(b" \t\r\nab").unwrap(), (&b"ab"[..], &b" \t\r\n"[..]));
+            assert!(blank_line::<()>(b"").is_err());
+            assert!(blank_line::<()>(b"  ").is_err());
+            assert!(blank_line::<()>(b" a\n").is_err());
+        }
+
+        #[test]
+        fn format_parsers() {
+            let mut robot = format_parser!("p={},{} v={},{}"; u8, u8, i8, i8);
+            let r: Res<'_, _> = robot.parse(b"p=0,4 v=3,-3\n");
+            assert_eq!(r.unwrap(), (&b"\n"[..], (0, 4, 3, -3)));
+
+            #[derive(Debug, PartialEq)]
+            struct Eqn<'a> {
+                name: &'a [u8],
+                result: u64,
+                nums: SmallVec<[u16; 4]>,
+            }
+            let mut eqn = format_parser!("{} = {}: {}" => Eqn { name: &[u8], result: u64, nums: SmallVec<[u16; 4]> });
+            let r: Res<'_, _> = eqn.parse(b"ab = 190: 10 19");
+            let nums = SmallVec::from_slice(&[10, 19]);
+            assert_eq!(r.unwrap().1, Eqn { name: b"ab", result: 190, nums });
+        }
+
+        #[test]
+        fn format_parser_errors() {
+            // Mismatched literal text
+            let r: Res<'_, _> = format_parser!("p={},{} v={},{}"; u8, u8, i8, i8).parse(b"p=0,4 w=3,-3");
+            let err = r.unwrap_err();
+            assert!(matches!(err, nom::Err::Error(e) if e.input == b" w=3,-3" && e.code == nom::error::ErrorKind::Tag));
+
+            // Out of range field
+            let r: Res<'_, _> = format_parser!("p={},{} v={},{}"; u8, u8, i8, i8).parse(b"p=0,400 v=3,-3");
+            assert!(r.is_err());
+
+            // Missing text after the last field
+            let r: Res<'_, _> = format_parser!("({})"; u8).parse(b"(5");
+            assert!(r.is_err());
+
+            let err = parse_lines(b"p=1,2 v=3,4\np=1 v=3,4\n", format_parser!("p={},{} v={},{}"; u8, u8, i8, i8));
+            assert_eq!(err.unwrap_err().to_string(), r#"Parse error (Tag): " v=3,4\n""#);
+        }
+
+        #[test]
+        fn format_placeholders() {
+            assert_eq!(placeholders("p={},{} v={},{}"), 4);
+            assert_eq!(placeholders("{}{}"), 2);
+            assert_eq!(placeholders("no fields"), 0);
+
+            assert!(FormatParser::<(u8, u8), ()>::new("{},{}").is_ok());
+            let err = FormatParser::<(u8, u8), ()>::new("{}").unwrap_err();
+            assert_eq!(err.to_string(), "Format string has 1 placeholders for 2 fields");
+            assert!(FormatParser::<(u8,), ()>::new("{} {}").is_err());
+        }
+
+        #[test]
+        fn adjacencies() {
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"AAA = (BBB, CCC)\nDDD");
+            assert_eq!(r.unwrap(), (&b"DDD"[..], (&b"AAA"[..], (&b"BBB"[..], &b"CCC"[..]))));
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"11A=(22B,XXX)");
+            assert_eq!(r.unwrap().1, (&b"11A"[..], (&b"22B"[..], &b"XXX"[..])));
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"AAA = (BBB, CCC)  \r\n");
+            assert_eq!(r.unwrap(), (&b""[..], (&b"AAA"[..], (&b"BBB"[..], &b"CCC"[..]))));
+
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"AAA = (BBB CCC)\n");
+            assert!(r.is_err());
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"AAA = (BBB, CCC\n");
+            assert!(r.is_err());
+            let r: Res<'_, Adjacency<&[u8]>> = adjacency(alphanumeric1)(b"AAA = (BBB, CCC) x\n");
+            assert!(r.is_err());
+        }
+    }
@@ -0,0 +1,138 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use snafu::{OptionExt, ResultExt};
+
+use super::{
+    render::{self, GifSnafu, IoSnafu, RenderError, Rgb, TooLargeSnafu},
+    view::GridView,
+};
+
+/// Collects snapshots of a grid simulation to be written out as an
+/// animation or as numbered frame files
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    width: usize,
+    height: usize,
+    scale: usize,
+    every: usize,
+    skip: usize,
+    frames: Vec<Vec<u8>>,
+    texts: Vec<String>,
+}
+
+impl Recorder {
+    /// Creates a recorder drawing each cell as a `scale` by `scale` square
+    pub fn new(scale: usize) -> Self {
+        Self {
+            scale: scale.max(1),
+            every: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a recorder if `record` was passed on the command line, so
+    /// simulations only pay for snapshots when asked
+    pub fn from_args(scale: usize) -> Option<Self> {
+        crate::has_arg("record").then(|| Self::new(scale))
+    }
+
+    /// Only keeps every `n`th image frame, so long simulations stay small
+    pub fn every(self, n: usize) -> Self {
+        Self { every: n, ..self }
+    }
+
+    /// Number of image frames recorded
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no image frames were recorded
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Records the current state of `grid` as an image frame, unless it is
+    /// skipped by [`every`](Self::every)
+    pub fn snapshot<G, F>(&mut self, grid: &G, color: F)
+    where
+        G: GridView,
+        F: Fn(G::Item) -> Rgb,
+    {
+        if let Some(left) = self.skip.checked_sub(1) {
+            self.skip = left;
+            return;
+        }
+        self.skip = self.every.saturating_sub(1);
+
+        let (width, height) = (grid.width() * self.scale, grid.height() * self.scale);
+        if self.frames.is_empty() {
+            (self.width, self.height) = (width, height);
+        }
+        assert_eq!(
+            (width, height),
+            (self.width, self.height),
+            "Frame size changed during recording"
+        );
+        self.frames.push(render::pixels(grid, self.scale, color));
+    }
+
+    /// Records the current state of `grid` as a text frame
+    pub fn snapshot_text<G, F>(&mut self, grid: &G, f: F)
+    where
+        G: GridView,
+        F: Fn(G::Item) -> char,
+    {
+        self.texts.push(grid.display_with(f).to_string());
+    }
+
+    /// Writes the image frames as a looping GIF, `delay` hundredths of a
+    /// second apart
+    pub fn write_gif<W: Write>(&self, delay: u16, out: W) -> Result<(), RenderError> {
+        let (w, h) = (self.width, self.height);
+        let too_large = TooLargeSnafu { width: w, height: h };
+        let width = u16::try_from(w).ok().context(too_large)?;
+        let height = u16::try_from(h).ok().context(too_large)?;
+
+        let mut enc = gif::Encoder::new(out, width, height, &[]).context(GifSnafu)?;
+        enc.set_repeat(gif::Repeat::Infinite).context(GifSnafu)?;
+        for data in &self.frames {
+            let mut frame = gif::Frame::from_rgb_speed(width, height, data, 10);
+            frame.delay = delay;
+            enc.write_frame(&frame).context(GifSnafu)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the image frames as a looping GIF at `path`
+    pub fn save_gif(&self, delay: u16, path: impl AsRef<Path>) -> Result<(), RenderError> {
+        let out = BufWriter::new(File::create(path).context(IoSnafu)?);
+        self.write_gif(delay, out)
+    }
+
+    /// Saves the image frames to `{name}.gif`, reporting where they went
+    pub fn finish(&self, name: &str) -> Result<(), RenderError> {
+        let path = format!("{name}.gif");
+        self.save_gif(5, &path)?;
+        println!("Saved {} frames to {path}", self.len());
+        Ok(())
+    }
+
+    /// Saves every frame into `dir` as `frame0000.ppm` for images and
+    /// `frame0000.txt` for text
+    pub fn save_frames(&self, dir: impl AsRef<Path>) -> Result<(), RenderError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).context(IoSnafu)?;
+        for (i, data) in self.frames.iter().enumerate() {
+            let out = BufWriter::new(File::create(dir.join(format!("frame{i:04}.ppm"))).context(IoSnafu)?);
+            render::write_ppm_data(self.width, self.height, data, out).context(IoSnafu)?;
+        }
+        for (i, text) in self.texts.iter().enumerate() {
+            fs::write(dir.join(format!("frame{i:04}.txt")), text).context(IoSnafu)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    sgrid::{Dir, Grid},
+    Coord,
+};
+
+/// Label of a tile not yet assigned to a component
+const UNLABELED: usize = usize::MAX;
+
+/// Connected components of a grid, as a label per tile
+#[derive(Debug, Clone)]
+pub struct Components {
+    labels: Box<[usize]>,
+    width: usize,
+    count: usize,
+}
+
+/// Size and shape measurements of a single component
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RegionMetrics {
+    /// Number of tiles in the region
+    pub area: u64,
+    /// Number of tile edges bordering another region or the grid edge
+    pub perimeter: u64,
+    /// Number of straight sides, equal to the number of corners
+    pub sides: u64,
+    /// Inclusive top-left and bottom-right corners
+    pub bounds: [Coord; 2],
+}
+
+impl<T> Grid<T> {
+    /// Finds every tile reachable from `start` through tiles matching `pred`.
+    /// Returns nothing if `start` itself does not match or is out of bounds.
+    pub fn flood_fill<F>(&self, start: usize, mut pred: F) -> Vec<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut seen = vec![false; self.len()];
+        let mut found = Vec::new();
+        let mut stack: Vec<_> = (start < self.len()).then_some(start).into_iter().collect();
+        while let Some(idx) = stack.pop() {
+            if seen[idx] || !pred(&self[idx]) {
+                continue;
+            }
+            seen[idx] = true;
+            found.push(idx);
+            stack.extend(Dir::ALL.into_iter().filter_map(|d| self.dir_index(idx, d)));
+        }
+        found
+    }
+
+    /// Labels the connected components of the grid, where orthogonal
+    /// neighbors are joined if `same` returns true for them
+    pub fn components<F>(&self, mut same: F) -> Components
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut labels = vec![UNLABELED; self.len()].into_boxed_slice();
+        let mut stack = Vec::new();
+        let mut count = 0;
+
+        for start in 0..self.len() {
+            if labels[start] != UNLABELED {
+                continue;
+            }
+
+            labels[start] = count;
+            stack.push(start);
+            while let Some(idx) = stack.pop() {
+                for dir in Dir::ALL {
+                    if let Some(ni) = self.dir_index(idx, dir) {
+                        if labels[ni] == UNLABELED && same(&self[idx], &self[ni]) {
+                            labels[ni] = count;
+                            stack.push(ni);
+                        }
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        Components {
+            labels,
+            width: self.width(),
+            count,
+        }
+    }
+
+    /// Labels the connected components made of equal tiles
+    pub fn components_eq(&self) -> Components
+    where
+        T: PartialEq,
+    {
+        self.components(T::eq)
+    }
+}
+
+impl Components {
+    /// Number of components
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Label of the component containing `idx`
+    pub fn label(&self, idx: usize) -> usize {
+        self.labels[idx]
+    }
+
+    /// Indices of every tile in the component `label`
+    pub fn tiles(&self, label: usize) -> impl Iterator<Item = usize> + '_ {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(move |&(_, &l)| l == label)
+            .map(|(i, _)| i)
+    }
+
+    /// Measures every component, indexed by label
+    pub fn metrics(&self) -> Vec<RegionMetrics> {
+        let mut metrics = vec![None::<RegionMetrics>; self.count];
+        for idx in 0..self.labels.len() {
+            let coord = Coord::of_idx(idx, self.width);
+            let perimeter = Dir::ALL
+                .into_iter()
+                .filter(|&d| !self.same_dir(idx, d))
+                .count() as u64;
+            let sides = u64::from(self.corners(idx));
+
+            match &mut metrics[self.labels[idx]] {
+                Some(m) => {
+                    m.area += 1;
+                    m.perimeter += perimeter;
+                    m.sides += sides;
+                    let [lo, hi] = &mut m.bounds;
+                    lo.x = lo.x.min(coord.x);
+                    lo.y = lo.y.min(coord.y);
+                    hi.x = hi.x.max(coord.x);
+                    hi.y = hi.y.max(coord.y);
+                }
+                m @ None => {
+                    *m = Some(RegionMetrics {
+                        area: 1,
+                        perimeter,
+                        sides,
+                        bounds: [coord, coord],
+                    })
+                }
+            }
+        }
+
+        metrics.into_iter().map(|m| m.expect("Empty component")).collect()
+    }
+
+    /// Index of the neighbor of `idx` in `dir` if it shares its component
+    fn dir_index(&self, idx: usize, dir: Dir) -> Option<usize> {
+        // An empty grid has no neighbors
+        let height = self.labels.len().checked_div(self.width)?;
+        let (x, y) = (idx % self.width, idx / self.width);
+        let ni = match dir {
+            Dir::Up => idx.checked_sub(self.width)?,
+            Dir::Down if y + 1 < height => idx + self.width,
+            Dir::Left if x > 0 => idx - 1,
+            Dir::Right if x + 1 < self.width => idx + 1,
+            _ => return None,
+        };
+        (self.labels[ni] == self.labels[idx]).then_some(ni)
+    }
+
+    fn same_dir(&self, idx: usize, dir: Dir) -> bool {
+        self.dir_index(idx, dir).is_some()
+    }
+
+    /// Number of corners of the component touching the tile `idx`
+    fn corners(&self, idx: usize) -> u8 {
+        let mut c = 0;
+        for d1 in Dir::ALL {
+            let d2 = d1.right();
+            c += match [self.same_dir(idx, d1), self.same_dir(idx, d2)] {
+                // Outer corner
+                [false, false] => 1,
+                // Inner corner, if the diagonal is outside
+                [true, true] => self
+                    .dir_index(idx, d1)
+                    .is_some_and(|i| !self.same_dir(i, d2)) as u8,
+                _ => 0,
+            }
+        }
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GARDEN: &[u8] = b"\
+AAAA
+BBCD
+BBCC
+EEEC
+";
+
+    fn garden() -> Grid<u8> {
+        Grid::parse(GARDEN, |b| b.is_ascii_uppercase().then_some(b)).unwrap().1
+    }
+
+    #[test]
+    fn flood_fill() {
+        let grid = garden();
+        let mut found = grid.flood_fill(2, |&b| b == b'A');
+        found.sort_unstable();
+        assert_eq!(found, [0, 1, 2, 3]);
+
+        assert_eq!(grid.flood_fill(6, |&b| b == b'C').len(), 4);
+        assert!(grid.flood_fill(0, |&b| b == b'B').is_empty());
+        assert!(grid.flood_fill(16, |_| true).is_empty());
+    }
+
+    #[test]
+    fn components() {
+        let grid = garden();
+        let comps = grid.components_eq();
+        assert_eq!(comps.count(), 5);
+        assert_eq!(comps.label(4), comps.label(9));
+        assert_ne!(comps.label(4), comps.label(6));
+        assert_eq!(comps.tiles(comps.label(6)).collect::<Vec<_>>(), [6, 10, 11, 15]);
+
+        let metrics = comps.metrics();
+        let areas: Vec<_> = metrics.iter().map(|m| m.area).collect();
+        let perimeters: Vec<_> = metrics.iter().map(|m| m.perimeter).collect();
+        let sides: Vec<_> = metrics.iter().map(|m| m.sides).collect();
+        assert_eq!(areas, [4, 4, 4, 1, 3]);
+        assert_eq!(perimeters, [10, 8, 10, 4, 8]);
+        assert_eq!(sides, [4, 4, 8, 4, 4]);
+        assert_eq!(metrics[2].bounds, [Coord { x: 2, y: 1 }, Coord { x: 3, y: 3 }]);
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid = Grid::<u8>::new(Vec::new(), 0);
+        let comps = grid.components_eq();
+        assert_eq!(comps.count(), 0);
+        assert!(comps.metrics().is_empty());
+        assert!(grid.flood_fill(0, |_| true).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use super::view::GridView;
+
+/// A 24-bit color
+pub type Rgb = [u8; 3];
+
+/// Picks a distinct, bright color for the `n`th label
+pub fn palette(n: usize) -> Rgb {
+    // Step the hue by the golden angle so neighbors differ
+    let hue = (n as f64 * 137.507_764) % 360.0;
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    [r, g, b].map(|c: f64| (c * 255.0) as u8)
+}
+
+/// Renders the grid to RGB pixel rows, each cell drawn as a `scale`
+/// by `scale` square
+pub(super) fn pixels<G, F>(grid: &G, scale: usize, color: F) -> Vec<u8>
+where
+    G: GridView,
+    F: Fn(G::Item) -> Rgb,
+{
+    let width = grid.width();
+    let row_len = width * scale * 3;
+    let mut data = Vec::with_capacity(row_len * grid.height() * scale);
+    for y in 0..grid.height() {
+        let start = data.len();
+        for x in 0..width {
+            let rgb = color(grid.get(y * width + x));
+            for _ in 0..scale {
+                data.extend_from_slice(&rgb);
+            }
+        }
+        for _ in 1..scale {
+            data.extend_from_within(start..start + row_len);
+        }
+    }
+    data
+}
+
+/// Writes the grid as a binary PPM image
+pub fn write_ppm<G, F, W>(grid: &G, scale: usize, color: F, mut out: W) -> io::Result<()>
+where
+    G: GridView,
+    F: Fn(G::Item) -> Rgb,
+    W: Write,
+{
+    let (w, h) = (grid.width() * scale, grid.height() * scale);
+    write_ppm_data(w, h, &pixels(grid, scale, color), out)
+}
+
+/// Writes RGB pixel rows of the given size as a binary PPM image
+pub(super) fn write_ppm_data<W: Write>(width: usize, height: usize, data: &[u8], mut out: W) -> io::Result<()> {
+    write!(out, "P6\n{width} {height}\n255\n")?;
+    out.write_all(data)?;
+    out.flush()
+}
+
+/// Writes the grid as a PNG image
+pub fn write_png<G, F, W>(grid: &G, scale: usize, color: F, out: W) -> Result<(), RenderError>
+where
+    G: GridView,
+    F: Fn(G::Item) -> Rgb,
+    W: Write,
+{
+    let (w, h) = (grid.width() * scale, grid.height() * scale);
+    let too_large = TooLargeSnafu { width: w, height: h };
+    let width = u32::try_from(w).ok().context(too_large)?;
+    let height = u32::try_from(h).ok().context(too_large)?;
+
+    let mut enc = png::Encoder::new(out, width, height);
+    enc.set_color(png::ColorType::Rgb);
+    enc.set_depth(png::BitDepth::Eight);
+    let mut writer = enc.write_header().context(PngSnafu)?;
+    writer
+        .write_image_data(&pixels(grid, scale, color))
+        .context(PngSnafu)
+}
+
+/// Saves the grid as an image at `path`, as PNG if the extension is
+/// `png` or PPM otherwise
+pub fn save_image<G, F>(grid: &G, scale: usize, color: F, path: impl AsRef<Path>) -> Result<(), RenderError>
+where
+    G: GridView,
+    F: Fn(G::Item) -> Rgb,
+{
+    let path = path.as_ref();
+    let out = BufWriter::new(File::create(path).context(IoSnafu)?);
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+        write_png(grid, scale, color, out)
+    } else {
+        write_ppm(grid, scale, color, out).context(IoSnafu)
+    }
+}
+
+/// Displays the grid in the terminal, with each cell drawn as the char
+/// and foreground color returned by `f`
+pub fn ansi<G, F>(grid: &G, f: F) -> Ansi<'_, G, F>
+where
+    G: GridView,
+    F: Fn(G::Item) -> (char, Rgb),
+{
+    Ansi { grid, f }
+}
+
+/// Colored terminal display of a grid, created by [`ansi`]
+pub struct Ansi<'g, G, F> {
+    grid: &'g G,
+    f: F,
+}
+
+impl<G: GridView, F: Fn(G::Item) -> (char, Rgb)> Display for Ansi<'_, G, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.grid.width();
+        let mut last = None;
+        for idx in 0..self.grid.len() {
+            if idx > 0 && idx % width == 0 {
+                f.write_str("\x1b[0m\n")?;
+                last = None;
+            }
+            let (c, rgb) = (self.f)(self.grid.get(idx));
+            if last != Some(rgb) {
+                let [r, g, b] = rgb;
+                write!(f, "\x1b[38;2;{r};{g};{b}m")?;
+                last = Some(rgb);
+            }
+            write!(f, "{c}")?;
+        }
+        f.write_str("\x1b[0m")
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(super)))]
+pub enum RenderError {
+    #[snafu(display("Failed to write image file"))]
+    Io { source: io::Error },
+    #[snafu(display("Failed to encode PNG"))]
+    Png { source: png::EncodingError },
+    #[snafu(display("Failed to encode GIF"))]
+    Gif { source: gif::EncodingError },
+    #[snafu(display("Image of {width}x{height} is too large"))]
+    TooLarge { width: usize, height: usize },
+}
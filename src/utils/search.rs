@@ -0,0 +1,593 @@
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use num::Zero;
+
+use super::sgrid::{Dir, Grid};
+
+/// Distances and predecessors found by a search
+#[derive(Debug, Clone)]
+pub struct Paths<S, C> {
+    dists: HashMap<S, C>,
+    preds: HashMap<S, S>,
+    goal: Option<S>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Paths<S, C> {
+    /// Shortest distance to `state`, if it was reached
+    pub fn dist(&self, state: &S) -> Option<C> {
+        self.dists.get(state).copied()
+    }
+
+    /// All reached states and their shortest distances
+    pub fn dists(&self) -> &HashMap<S, C> {
+        &self.dists
+    }
+
+    /// Previous state on the shortest path to `state`, `None` for starts
+    pub fn pred(&self, state: &S) -> Option<&S> {
+        self.preds.get(state)
+    }
+
+    /// The goal state the search stopped at
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.as_ref()
+    }
+
+    /// Shortest distance to the goal state
+    pub fn goal_dist(&self) -> Option<C> {
+        self.dist(self.goal.as_ref()?)
+    }
+
+    /// Shortest path from a start to `state`, inclusive of both ends
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        if !self.dists.contains_key(state) {
+            return None;
+        }
+
+        let mut path = vec![state.clone()];
+        while let Some(prev) = self.preds.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Shortest path from a start to the goal state
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+/// Distances and every optimal predecessor found by an exhaustive search
+#[derive(Debug, Clone)]
+pub struct AllPaths<S, C> {
+    dists: HashMap<S, C>,
+    preds: HashMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> AllPaths<S, C> {
+    /// Shortest distance to `state`, if it was reached
+    pub fn dist(&self, state: &S) -> Option<C> {
+        self.dists.get(state).copied()
+    }
+
+    /// All reached states and their shortest distances
+    pub fn dists(&self) -> &HashMap<S, C> {
+        &self.dists
+    }
+
+    /// Every previous state on a shortest path to `state`
+    pub fn preds(&self, state: &S) -> &[S] {
+        self.preds.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every state on any shortest path from a start to one of `ends`
+    pub fn on_paths_to(&self, ends: impl IntoIterator<Item = S>) -> HashSet<S> {
+        let mut seen = HashSet::new();
+        let mut stack: Vec<S> = ends
+            .into_iter()
+            .filter(|e| self.dists.contains_key(e))
+            .collect();
+        while let Some(state) = stack.pop() {
+            if seen.insert(state.clone()) {
+                stack.extend_from_slice(self.preds(&state));
+            }
+        }
+        seen
+    }
+}
+
+/// Step distances to every tile from both ends of a grid path
+#[derive(Debug, Clone)]
+pub struct DistFields {
+    /// Distance from the start to each tile
+    pub from_start: Box<[Option<usize>]>,
+    /// Distance from each tile to the end
+    pub to_end: Box<[Option<usize>]>,
+    end: usize,
+}
+
+impl DistFields {
+    /// Length of the shortest path from start to end
+    pub fn total(&self) -> Option<usize> {
+        self.from_start[self.end]
+    }
+
+    /// Length of the path from start to `a`, then `steps` to `b`, then to the end
+    pub fn through(&self, a: usize, steps: usize, b: usize) -> Option<usize> {
+        Some(self.from_start[a]? + steps + self.to_end[b]?)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Step distances from `start` to every tile reachable through
+    /// tiles matching `open`
+    pub fn distances<F>(&self, start: usize, mut open: F) -> Box<[Option<usize>]>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut dists = vec![None; self.len()].into_boxed_slice();
+        let mut queue = VecDeque::new();
+        if open(&self[start]) {
+            dists[start] = Some(0);
+            queue.push_back(start);
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            let ndist = dists[idx].map(|d| d + 1);
+            for dir in Dir::ALL {
+                if let Some(ni) = self.dir_index(idx, dir) {
+                    if dists[ni].is_none() && open(&self[ni]) {
+                        dists[ni] = ndist;
+                        queue.push_back(ni);
+                    }
+                }
+            }
+        }
+
+        dists
+    }
+
+    /// Step distances from `start` and to `end` for every tile
+    pub fn dist_fields<F>(&self, start: usize, end: usize, mut open: F) -> DistFields
+    where
+        F: FnMut(&T) -> bool,
+    {
+        DistFields {
+            from_start: self.distances(start, &mut open),
+            to_end: self.distances(end, open),
+            end,
+        }
+    }
+}
+
+/// Breadth-first search from `starts` where every step costs 1,
+/// stopping at the first state matching `goal`
+pub fn bfs<S, I, F, G>(starts: impl IntoIterator<Item = S>, mut succ: F, mut goal: G) -> Paths<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+    F: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    let mut paths = Paths {
+        dists: HashMap::new(),
+        preds: HashMap::new(),
+        goal: None,
+    };
+
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(e) = paths.dists.entry(start.clone()) {
+            e.insert(0);
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((state, dist)) = queue.pop_front() {
+        if goal(&state) {
+            paths.goal = Some(state);
+            break;
+        }
+
+        for next in succ(&state) {
+            if let Entry::Vacant(e) = paths.dists.entry(next.clone()) {
+                e.insert(dist + 1);
+                paths.preds.insert(next.clone(), state.clone());
+                queue.push_back((next, dist + 1));
+            }
+        }
+    }
+
+    paths
+}
+
+/// Dijkstra's algorithm from `starts` over weighted successors,
+/// stopping at the first state matching `goal`
+pub fn dijkstra<S, C, I, F, G>(starts: impl IntoIterator<Item = S>, succ: F, goal: G) -> Paths<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (S, C)>,
+    F: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    astar(starts, succ, |_| C::zero(), goal)
+}
+
+/// Dial's algorithm from `starts` over successors with small integer
+/// costs no greater than `max_step`, stopping at the first state
+/// matching `goal`
+pub fn dial<S, I, F, G>(
+    starts: impl IntoIterator<Item = S>,
+    max_step: usize,
+    mut succ: F,
+    mut goal: G,
+) -> Paths<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+    F: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    let mut paths = Paths {
+        dists: HashMap::new(),
+        preds: HashMap::new(),
+        goal: None,
+    };
+
+    // Circular buckets of states by distance
+    let mut buckets = vec![Vec::new(); max_step + 1];
+    let mut pending = 0usize;
+    for start in starts {
+        paths.dists.insert(start.clone(), 0);
+        buckets[0].push(start);
+        pending += 1;
+    }
+
+    let mut cost = 0;
+    while pending > 0 {
+        let slot = cost % buckets.len();
+        while let Some(state) = buckets[slot].pop() {
+            pending -= 1;
+
+            // Skip outdated entries
+            if paths.dists.get(&state) != Some(&cost) {
+                continue;
+            }
+
+            if goal(&state) {
+                paths.goal = Some(state);
+                return paths;
+            }
+
+            for (next, step) in succ(&state) {
+                assert!(step <= max_step, "Step cost {step} exceeds maximum {max_step}");
+                let ncost = cost + step;
+                let better = match paths.dists.entry(next.clone()) {
+                    Entry::Occupied(mut e) if ncost < *e.get() => {
+                        e.insert(ncost);
+                        true
+                    }
+                    Entry::Occupied(_) => false,
+                    Entry::Vacant(e) => {
+                        e.insert(ncost);
+                        true
+                    }
+                };
+
+                if better {
+                    paths.preds.insert(next.clone(), state.clone());
+                    buckets[ncost % (max_step + 1)].push(next);
+                    pending += 1;
+                }
+            }
+        }
+        cost += 1;
+    }
+
+    paths
+}
+
+/// A* search from `starts` over weighted successors, stopping at the
+/// first state matching `goal`. The `heuristic` must never overestimate
+/// the remaining distance to a goal. It doesn't have to be consistent, as
+/// a state is expanded again whenever a cheaper path to it is found.
+pub fn astar<S, C, I, F, H, G>(
+    starts: impl IntoIterator<Item = S>,
+    mut succ: F,
+    mut heuristic: H,
+    mut goal: G,
+) -> Paths<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (S, C)>,
+    F: FnMut(&S) -> I,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    let mut paths = Paths {
+        dists: HashMap::new(),
+        preds: HashMap::new(),
+        goal: None,
+    };
+
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        paths.dists.insert(start.clone(), C::zero());
+        heap.push(Item {
+            est: heuristic(&start),
+            cost: C::zero(),
+            state: start,
+        });
+    }
+
+    while let Some(Item { cost, state, .. }) = heap.pop() {
+        // Skip entries superseded by a cheaper path
+        if paths.dists.get(&state).is_some_and(|&best| cost > best) {
+            continue;
+        }
+
+        if goal(&state) {
+            paths.goal = Some(state);
+            break;
+        }
+
+        for (next, step) in succ(&state) {
+            let ncost = cost + step;
+            let better = match paths.dists.entry(next.clone()) {
+                Entry::Occupied(mut e) if ncost < *e.get() => {
+                    e.insert(ncost);
+                    true
+                }
+                Entry::Occupied(_) => false,
+                Entry::Vacant(e) => {
+                    e.insert(ncost);
+                    true
+                }
+            };
+
+            if better {
+                paths.preds.insert(next.clone(), state.clone());
+                heap.push(Item {
+                    est: ncost + heuristic(&next),
+                    cost: ncost,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    paths
+}
+
+/// Exhaustive Dijkstra's algorithm from `starts`, recording every
+/// predecessor that lies on a shortest path
+pub fn dijkstra_all<S, C, I, F>(starts: impl IntoIterator<Item = S>, mut succ: F) -> AllPaths<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (S, C)>,
+    F: FnMut(&S) -> I,
+{
+    let mut paths = AllPaths {
+        dists: HashMap::new(),
+        preds: HashMap::new(),
+    };
+
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        paths.dists.insert(start.clone(), C::zero());
+        heap.push(Item {
+            est: C::zero(),
+            cost: C::zero(),
+            state: start,
+        });
+    }
+
+    let mut done = HashSet::new();
+    while let Some(Item { cost, state, .. }) = heap.pop() {
+        // Skip outdated entries
+        if !done.insert(state.clone()) {
+            continue;
+        }
+
+        for (next, step) in succ(&state) {
+            let ncost = cost + step;
+            match paths.dists.entry(next.clone()) {
+                Entry::Occupied(e) if ncost > *e.get() => continue,
+                Entry::Occupied(e) if ncost == *e.get() => {
+                    paths.preds.entry(next).or_default().push(state.clone());
+                    continue;
+                }
+                Entry::Occupied(mut e) => _ = e.insert(ncost),
+                Entry::Vacant(e) => _ = e.insert(ncost),
+            }
+
+            paths.preds.insert(next.clone(), vec![state.clone()]);
+            heap.push(Item {
+                est: ncost,
+                cost: ncost,
+                state: next,
+            });
+        }
+    }
+
+    paths
+}
+
+/// Heap entry, ordered so the lowest estimate is popped first
+struct Item<S, C> {
+    est: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Item<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.est == other.est
+    }
+}
+
+impl<S, C: Ord> Eq for Item<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Item<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Item<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.est.cmp(&self.est)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two equally short routes from the top left to the bottom of column 3,
+    // plus open tiles walled off from the start
+    const MAZE: &[u8] = b"\
+....#.
+.##.##
+....#.
+###.#.
+";
+
+    // Entry costs with two routes of cost 6 to the bottom right of the
+    // left block, and an unreachable column on the right
+    const COSTS: &[u8] = b"\
+131#1
+1#1#1
+311#1
+";
+
+    fn maze() -> Grid<u8> {
+        Grid::parse(MAZE, |b| matches!(b, b'.' | b'#').then_some(b)).unwrap().1
+    }
+
+    fn costs() -> Grid<u8> {
+        Grid::parse(COSTS, |b| (b == b'#' || b.is_ascii_digit()).then_some(b)).unwrap().1
+    }
+
+    fn moves(grid: &Grid<u8>, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        Dir::ALL
+            .into_iter()
+            .filter_map(move |dir| grid.dir_index(idx, dir))
+            .filter(|&ni| grid[ni] != b'#')
+    }
+
+    fn weighted(grid: &Grid<u8>, idx: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        moves(grid, idx).map(|ni| (ni, usize::from(grid[ni] - b'0')))
+    }
+
+    fn assert_steps(grid: &Grid<u8>, path: &[usize]) {
+        for w in path.windows(2) {
+            assert!(moves(grid, w[0]).any(|ni| ni == w[1]), "{} -> {} is not a move", w[0], w[1]);
+        }
+    }
+
+    #[test]
+    fn bfs_grid() {
+        let grid = maze();
+        let paths = bfs([0], |&i| moves(&grid, i), |&i| i == 21);
+        assert_eq!(paths.goal(), Some(&21));
+        assert_eq!(paths.goal_dist(), Some(6));
+
+        let path = paths.path().unwrap();
+        assert_eq!((path.len(), path[0], path[6]), (7, 0, 21));
+        assert_steps(&grid, &path);
+
+        let paths = bfs([0], |&i| moves(&grid, i), |&i| i == 5);
+        assert_eq!(paths.goal(), None);
+        assert_eq!(paths.goal_dist(), None);
+        assert_eq!(paths.path(), None);
+        assert_eq!(paths.dists().len(), 11);
+    }
+
+    #[test]
+    fn dijkstra_grid() {
+        let grid = costs();
+        let paths = dijkstra([0], |&i| weighted(&grid, i), |&i| i == 12);
+        assert_eq!(paths.goal_dist(), Some(6));
+        let path = paths.path().unwrap();
+        assert_eq!((path[0], path[path.len() - 1]), (0, 12));
+        assert_steps(&grid, &path);
+
+        let paths = dijkstra([0], |&i| weighted(&grid, i), |&i| i == 4);
+        assert_eq!(paths.goal_dist(), None);
+        assert_eq!(paths.dist(&10), Some(4));
+        assert_eq!(paths.dist(&9), None);
+    }
+
+    #[test]
+    fn dial_grid() {
+        let grid = costs();
+        let paths = dial([0], 3, |&i| weighted(&grid, i), |&i| i == 12);
+        assert_eq!(paths.goal_dist(), Some(6));
+        assert_steps(&grid, &paths.path().unwrap());
+
+        let paths = dial([0], 3, |&i| weighted(&grid, i), |&i| i == 4);
+        assert_eq!(paths.goal_dist(), None);
+        assert_eq!(paths.dist(&11), Some(5));
+    }
+
+    #[test]
+    fn dijkstra_all_grid() {
+        let grid = costs();
+        let paths = dijkstra_all([0], |&i| weighted(&grid, i));
+        assert_eq!(paths.dist(&12), Some(6));
+        assert_eq!(paths.dist(&4), None);
+
+        // Both routes end in the same tile
+        let mut preds = paths.preds(&12).to_vec();
+        preds.sort_unstable();
+        assert_eq!(preds, [7, 11]);
+
+        let mut on_paths: Vec<_> = paths.on_paths_to([12]).into_iter().collect();
+        on_paths.sort_unstable();
+        assert_eq!(on_paths, [0, 1, 2, 5, 7, 10, 11, 12]);
+        assert!(paths.on_paths_to([4]).is_empty());
+    }
+
+    #[test]
+    fn dist_fields() {
+        let grid = maze();
+        let fields = grid.dist_fields(0, 21, |&b| b != b'#');
+        assert_eq!(fields.total(), Some(6));
+        assert_eq!(fields.from_start[15], Some(5));
+        assert_eq!(fields.to_end[0], Some(6));
+        assert_eq!(fields.to_end[9], Some(2));
+        assert_eq!(fields.through(3, 1, 9), Some(6));
+        assert_eq!(fields.through(0, 2, 2), Some(6));
+
+        assert_eq!(fields.from_start[5], None);
+        assert_eq!(fields.to_end[23], None);
+        assert_eq!(fields.through(0, 1, 5), None);
+
+        let walled = grid.dist_fields(0, 5, |&b| b != b'#');
+        assert_eq!(walled.total(), None);
+    }
+
+    #[test]
+    fn astar_reopens_states() {
+        // The heuristic is admissible but not consistent at `b`, so `a` is
+        // first expanded through the more expensive direct edge
+        let edges: &[(char, char, u32)] = &[('s', 'a', 4), ('s', 'b', 1), ('b', 'a', 1), ('a', 'g', 5)];
+        let paths = astar(
+            ['s'],
+            |&n| edges.iter().filter(move |e| e.0 == n).map(|e| (e.1, e.2)),
+            |&n| if n == 'b' { 5 } else { 0 },
+            |&n| n == 'g',
+        );
+        assert_eq!(paths.goal_dist(), Some(7));
+        assert_eq!(paths.path().unwrap(), ['s', 'b', 'a', 'g']);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    io::{self, BufRead},
+};
+
+use nom::IResult;
+use snafu::{ResultExt, Snafu};
+
+use super::{eof_iterator, NomFail};
+
+/// Minimum number of bytes of whole lines to parse at once
+const CHUNK: usize = 64 * 1024;
+
+/// Parses records from `reader` a chunk of lines at a time, rather than
+/// loading the whole input into memory.
+///
+/// `parser` must be a function taking any input lifetime, and its records
+/// must not borrow from the input. A record may span several lines, as long
+/// as parsing a cut off record fails at the end of the input, so that it can
+/// be retried with more lines. A failure anywhere else is reported as soon
+/// as its chunk is parsed.
+pub fn records<R, O, F>(reader: R, parser: F) -> Records<R, O, F>
+where
+    R: BufRead,
+    F: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    Records {
+        reader,
+        parser,
+        buf: Vec::new(),
+        ready: VecDeque::new(),
+        eof: false,
+        error: None,
+        done: false,
+    }
+}
+
+/// Iterator over records parsed from a reader, created by [`records`]
+pub struct Records<R, O, F> {
+    reader: R,
+    parser: F,
+    buf: Vec<u8>,
+    ready: VecDeque<O>,
+    eof: bool,
+    error: Option<StreamError>,
+    done: bool,
+}
+
+impl<R, O, F> Records<R, O, F>
+where
+    R: BufRead,
+    F: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    /// Reads and parses lines until at least one record is ready or the
+    /// input is exhausted
+    fn fill(&mut self) -> Result<(), StreamError> {
+        let mut target = CHUNK;
+        while self.ready.is_empty() {
+            while self.buf.len() < target && !self.eof {
+                let n = self.reader.read_until(b'\n', &mut self.buf).context(IoSnafu)?;
+                self.eof = n == 0;
+            }
+            if self.buf.is_empty() {
+                return Ok(());
+            }
+
+            // Track where the last whole record ended
+            let len = self.buf.len();
+            let consumed = Cell::new(0);
+            let parser = &mut self.parser;
+            let mut iter = eof_iterator(&self.buf[..], |input| {
+                let res = parser(input);
+                if let Ok((rest, _)) = &res {
+                    consumed.set(len - rest.len());
+                }
+                res
+            });
+            self.ready.extend(iter.by_ref());
+
+            // A record cut off by the end of the chunk fails only once it
+            // runs out of input, and is retried with more lines. Any other
+            // failure is reported straight away.
+            let err = match iter.finish() {
+                Ok(_) => {
+                    self.buf.clear();
+                    continue;
+                }
+                Err(nom::Err::Incomplete(_)) if !self.eof => None,
+                Err(nom::Err::Error(e)) if !self.eof && e.input.is_empty() => None,
+                Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Some(NomFail::from(e)),
+                Err(nom::Err::Incomplete(_)) => Some(NomFail::from(nom::error::Error::new(
+                    &self.buf[consumed.get()..],
+                    nom::error::ErrorKind::Eof,
+                ))),
+            };
+            if let Some(source) = err {
+                return Err(StreamError::Parse { source });
+            }
+
+            self.buf.drain(..consumed.get());
+            target = CHUNK.max(self.buf.len() + 1);
+        }
+        Ok(())
+    }
+}
+
+impl<R, O, F> Iterator for Records<R, O, F>
+where
+    R: BufRead,
+    F: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    type Item = Result<O, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ready.is_empty() && self.error.is_none() && !self.done {
+            // Records parsed before an error are still yielded first
+            if let Err(e) = self.fill() {
+                self.error = Some(e);
+            }
+        }
+        if let Some(rec) = self.ready.pop_front() {
+            return Some(Ok(rec));
+        }
+        self.done = true;
+        self.error.take().map(Err)
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum StreamError {
+    #[snafu(display("Failed to read input"))]
+    Io { source: io::Error },
+    #[snafu(display("Failed to parse input"))]
+    Parse { source: NomFail },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use nom::sequence::pair;
+
+    use super::*;
+    use crate::utils::parser::{int, line};
+
+    fn num(input: &[u8]) -> IResult<&[u8], u32> {
+        line(int)(input)
+    }
+
+    fn num_pair(input: &[u8]) -> IResult<&[u8], (u32, u32)> {
+        pair(line(int), line(int))(input)
+    }
+
+    /// Counts the bytes taken from the wrapped reader
+    struct Counting<'a> {
+        inner: &'a [u8],
+        read: &'a Cell<usize>,
+    }
+
+    impl Read for Counting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read.set(self.read.get() + n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn records_across_chunks() {
+        let input: String = (0..50_000).map(|n| format!("{n}\n")).collect();
+        let pairs: Vec<_> = records(input.as_bytes(), num_pair).collect::<Result<_, _>>().unwrap();
+        assert_eq!(pairs.len(), 25_000);
+        assert!(pairs.iter().enumerate().all(|(i, &(a, b))| (a, b) == (2 * i as u32, 2 * i as u32 + 1)));
+    }
+
+    #[test]
+    fn early_error() {
+        let input: String = ["1\n2\nx\n".to_string()]
+            .into_iter()
+            .chain((0..100_000).map(|n| format!("{n}\n")))
+            .collect();
+        let read = Cell::new(0);
+        let reader = io::BufReader::new(Counting { inner: input.as_bytes(), read: &read });
+
+        let mut iter = records(reader, num);
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.next().unwrap().unwrap(), 2);
+        assert!(matches!(iter.next(), Some(Err(StreamError::Parse { .. }))));
+        assert!(iter.next().is_none());
+        assert!(read.get() < 2 * CHUNK, "Read {} bytes before the error", read.get());
+    }
+
+    #[test]
+    fn cut_off_at_eof() {
+        let mut iter = records(&b"1\n2\n3\n"[..], num_pair);
+        assert_eq!(iter.next().unwrap().unwrap(), (1, 2));
+        assert!(matches!(iter.next(), Some(Err(StreamError::Parse { .. }))));
+    }
+}
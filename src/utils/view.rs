@@ -0,0 +1,352 @@
+use std::{
+    fmt::{self, Display, Write},
+    ops::Index,
+};
+
+use super::{
+    sgrid::{Dir, Grid},
+    Coord,
+};
+
+/// Read-only access to a rectangular grid of values, which may be
+/// computed on demand rather than stored
+pub trait GridView {
+    type Item;
+
+    /// Number of columns
+    fn width(&self) -> usize;
+
+    /// Number of rows
+    fn height(&self) -> usize;
+
+    /// Value at `idx`, in row-major order
+    fn get(&self, idx: usize) -> Self::Item;
+
+    /// Total number of cells
+    fn len(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Whether there are no cells
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the cell next to `idx` in `dir`, if within bounds
+    fn dir_index(&self, idx: usize, dir: Dir) -> Option<usize> {
+        let width = self.width();
+        let (x, y) = (idx.checked_rem(width)?, idx / width);
+        match dir {
+            Dir::Up => idx.checked_sub(width),
+            Dir::Down if y + 1 < self.height() => Some(idx + width),
+            Dir::Left if x > 0 => Some(idx - 1),
+            Dir::Right if x + 1 < width => Some(idx + 1),
+            _ => None,
+        }
+    }
+
+    /// Displays each cell as the char returned by `f`, one row per line
+    fn display_with<F>(&self, f: F) -> impl Display
+    where
+        F: Fn(Self::Item) -> char,
+        Self: Sized,
+    {
+        ViewDisplay { view: self, f }
+    }
+
+    /// Copies every value into a new grid
+    fn to_grid(&self) -> Grid<Self::Item> {
+        let elems = (0..self.len()).map(|i| self.get(i)).collect();
+        Grid::new(elems, self.width())
+    }
+}
+
+impl<T: Clone> GridView for Grid<T> {
+    type Item = T;
+
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn get(&self, idx: usize) -> T {
+        self[idx].clone()
+    }
+}
+
+/// A grid repeated in both directions, with each copy's values
+/// transformed by its tile position. When the transform returns
+/// references into the grid, the view can also be indexed like one.
+pub struct Tiled<'g, T, F> {
+    grid: &'g Grid<T>,
+    tiles_x: usize,
+    tiles_y: usize,
+    f: F,
+}
+
+/// A grid with every cell expanded into a block of cells. When the
+/// transform returns references into the grid, the view can also be
+/// indexed like one.
+pub struct Scaled<'g, T, F> {
+    grid: &'g Grid<T>,
+    scale_x: usize,
+    scale_y: usize,
+    f: F,
+}
+
+impl<T> Grid<T> {
+    /// Repeats the grid `tiles_x` times across and `tiles_y` times down.
+    /// Values are produced by `f` from the original value and the tile's
+    /// `(x, y)` position.
+    pub fn tiled<'g, U, F>(&'g self, tiles_x: usize, tiles_y: usize, f: F) -> Tiled<'g, T, F>
+    where
+        F: Fn(&'g T, usize, usize) -> U,
+    {
+        Tiled {
+            grid: self,
+            tiles_x,
+            tiles_y,
+            f,
+        }
+    }
+
+    /// Expands every cell into a `scale_x` by `scale_y` block. Values are
+    /// produced by `f` from the original value and the `(x, y)` position
+    /// within the block.
+    pub fn scaled<'g, U, F>(&'g self, scale_x: usize, scale_y: usize, f: F) -> Scaled<'g, T, F>
+    where
+        F: Fn(&'g T, usize, usize) -> U,
+    {
+        Scaled {
+            grid: self,
+            scale_x,
+            scale_y,
+            f,
+        }
+    }
+}
+
+impl<'g, T, F> Tiled<'g, T, F> {
+    /// Original value and tile position behind `idx`
+    fn source(&self, idx: usize) -> (&'g T, usize, usize) {
+        let grid = self.grid;
+        let (gw, gh) = (grid.width(), grid.height());
+        let (x, y) = (idx % (gw * self.tiles_x), idx / (gw * self.tiles_x));
+        (&grid[(y % gh) * gw + (x % gw)], x / gw, y / gh)
+    }
+}
+
+impl<'g, T, U, F> GridView for Tiled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> U,
+{
+    type Item = U;
+
+    fn width(&self) -> usize {
+        self.grid.width() * self.tiles_x
+    }
+
+    fn height(&self) -> usize {
+        rows(self.grid) * self.tiles_y
+    }
+
+    fn get(&self, idx: usize) -> U {
+        let (value, tx, ty) = self.source(idx);
+        (self.f)(value, tx, ty)
+    }
+}
+
+impl<'g, T, U: ?Sized + 'g, F> Index<usize> for Tiled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> &'g U,
+{
+    type Output = U;
+
+    fn index(&self, idx: usize) -> &U {
+        let (value, tx, ty) = self.source(idx);
+        (self.f)(value, tx, ty)
+    }
+}
+
+impl<'g, T, U: ?Sized + 'g, F> Index<Coord> for Tiled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> &'g U,
+{
+    type Output = U;
+
+    fn index(&self, pos: Coord) -> &U {
+        &self[pos.to_idx(self.grid.width() * self.tiles_x)]
+    }
+}
+
+impl<'g, T, F> Scaled<'g, T, F> {
+    /// Original value and position within its block behind `idx`
+    fn source(&self, idx: usize) -> (&'g T, usize, usize) {
+        let grid = self.grid;
+        let width = grid.width() * self.scale_x;
+        let (x, y) = (idx % width, idx / width);
+        let inner = (y / self.scale_y) * grid.width() + (x / self.scale_x);
+        (&grid[inner], x % self.scale_x, y % self.scale_y)
+    }
+}
+
+impl<'g, T, U, F> GridView for Scaled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> U,
+{
+    type Item = U;
+
+    fn width(&self) -> usize {
+        self.grid.width() * self.scale_x
+    }
+
+    fn height(&self) -> usize {
+        rows(self.grid) * self.scale_y
+    }
+
+    fn get(&self, idx: usize) -> U {
+        let (value, dx, dy) = self.source(idx);
+        (self.f)(value, dx, dy)
+    }
+}
+
+impl<'g, T, U: ?Sized + 'g, F> Index<usize> for Scaled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> &'g U,
+{
+    type Output = U;
+
+    fn index(&self, idx: usize) -> &U {
+        let (value, dx, dy) = self.source(idx);
+        (self.f)(value, dx, dy)
+    }
+}
+
+impl<'g, T, U: ?Sized + 'g, F> Index<Coord> for Scaled<'g, T, F>
+where
+    F: Fn(&'g T, usize, usize) -> &'g U,
+{
+    type Output = U;
+
+    fn index(&self, pos: Coord) -> &U {
+        &self[pos.to_idx(self.grid.width() * self.scale_x)]
+    }
+}
+
+/// Number of rows in `grid`, which is 0 when it has no columns
+fn rows<T>(grid: &Grid<T>) -> usize {
+    grid.len().checked_div(grid.width()).unwrap_or(0)
+}
+
+struct ViewDisplay<'v, V, F> {
+    view: &'v V,
+    f: F,
+}
+
+impl<V: GridView, F: Fn(V::Item) -> char> Display for ViewDisplay<'_, V, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.view.width();
+        for idx in 0..self.view.len() {
+            if idx > 0 && idx % width == 0 {
+                f.write_char('\n')?;
+            }
+            f.write_char((self.f)(self.view.get(idx)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Grid<u8> {
+        Grid::new(vec![1, 2, 3, 4], 2)
+    }
+
+    #[test]
+    fn tiled() {
+        let grid = base();
+        let view = grid.tiled(3, 2, |&v, tx, ty| v + (tx + ty) as u8);
+        assert_eq!((view.width(), view.height(), view.len()), (6, 4, 24));
+
+        #[rustfmt::skip]
+        let expected = [
+            1, 2, 2, 3, 3, 4,
+            3, 4, 4, 5, 5, 6,
+            2, 3, 3, 4, 4, 5,
+            4, 5, 5, 6, 6, 7,
+        ];
+        let values: Vec<_> = (0..view.len()).map(|i| view.get(i)).collect();
+        assert_eq!(values, expected);
+        assert_eq!(view.to_grid().into_inner(), expected);
+        assert_eq!(
+            view.display_with(|v| char::from(b'0' + v)).to_string(),
+            "122334\n344556\n233445\n455667"
+        );
+    }
+
+    #[test]
+    fn tiled_index() {
+        let grid = base();
+        let view = grid.tiled(3, 2, |v, _, _| v);
+        let copy = view.to_grid();
+        for idx in 0..view.len() {
+            assert_eq!(view[idx], *copy[idx]);
+        }
+        assert_eq!(view[Coord { x: 5, y: 3 }], 4);
+        assert_eq!(view[Coord { x: 2, y: 1 }], 3);
+    }
+
+    #[test]
+    fn scaled() {
+        let grid = base();
+        let view = grid.scaled(2, 3, |&v, dx, dy| v * 10 + (dy * 2 + dx) as u8);
+        assert_eq!((view.width(), view.height()), (4, 6));
+
+        #[rustfmt::skip]
+        let expected = [
+            10, 11, 20, 21,
+            12, 13, 22, 23,
+            14, 15, 24, 25,
+            30, 31, 40, 41,
+            32, 33, 42, 43,
+            34, 35, 44, 45,
+        ];
+        let values: Vec<_> = (0..view.len()).map(|i| view.get(i)).collect();
+        assert_eq!(values, expected);
+        assert_eq!(view.to_grid().into_inner(), expected);
+    }
+
+    #[test]
+    fn scaled_index() {
+        let grid = base();
+        let view = grid.scaled(2, 3, |v, dx, _| if dx == 0 { v } else { &0 });
+        let copy = view.to_grid();
+        for idx in 0..view.len() {
+            assert_eq!(view[idx], *copy[idx]);
+        }
+        assert_eq!(view[Coord { x: 2, y: 4 }], 4);
+        assert_eq!(view[Coord { x: 3, y: 4 }], 0);
+    }
+
+    #[test]
+    fn dir_index() {
+        let grid = base();
+        let view = grid.tiled(3, 2, |&v, _, _| v);
+        assert_eq!(view.dir_index(5, Dir::Right), None);
+        assert_eq!(view.dir_index(5, Dir::Down), Some(11));
+        assert_eq!(view.dir_index(6, Dir::Left), None);
+        assert_eq!(view.dir_index(20, Dir::Down), None);
+        assert_eq!(view.dir_index(20, Dir::Up), Some(14));
+
+        let empty = Grid::<u8>::new(Vec::new(), 0);
+        let view = empty.scaled(2, 2, |&v, _, _| v);
+        assert!(view.is_empty());
+        assert_eq!(view.dir_index(0, Dir::Right), None);
+        assert_eq!(view.dir_index(0, Dir::Up), None);
+    }
+}
@@ -0,0 +1,337 @@
+use std::{collections::HashSet, fmt};
+
+use snafu::{ensure, OptionExt, Snafu};
+
+pub type Reg = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    /// A = A >> combo
+    Adv,
+    /// B ^= literal
+    Bxl,
+    /// B = combo % 8
+    Bst,
+    /// Jump to literal if A != 0
+    Jnz,
+    /// B ^= C
+    Bxc,
+    /// Output combo % 8
+    Out,
+    /// B = A >> combo
+    Bdv,
+    /// C = A >> combo
+    Cdv,
+}
+
+impl Opcode {
+    const ALL: [Opcode; 8] = [
+        Opcode::Adv,
+        Opcode::Bxl,
+        Opcode::Bst,
+        Opcode::Jnz,
+        Opcode::Bxc,
+        Opcode::Out,
+        Opcode::Bdv,
+        Opcode::Cdv,
+    ];
+
+    fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
+
+    /// Whether the operand is a combo operand rather than a literal
+    fn takes_combo(self) -> bool {
+        matches!(self, Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv)
+    }
+}
+
+/// A decoded instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instr {
+    pub op: Opcode,
+    pub operand: u8,
+}
+
+impl Instr {
+    /// Decodes the instruction at `ptr`, checking its opcode and operand
+    pub fn decode(op: u8, operand: u8, ptr: usize) -> Result<Self, VmError> {
+        let op = *Opcode::ALL.get(usize::from(op)).context(OpcodeSnafu { op, ptr })?;
+        ensure!(operand < 8 && !(op.takes_combo() && operand == 7), OperandSnafu { operand, ptr });
+        Ok(Instr { op, operand })
+    }
+}
+
+/// Renders combo operands as the register they read
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = self.op.mnemonic();
+        match self.operand {
+            _ if self.op == Opcode::Bxc => f.write_str(op),
+            4..=6 if self.op.takes_combo() => write!(f, "{op} {}", (b'A' + self.operand - 4) as char),
+            val => write!(f, "{op} {val}"),
+        }
+    }
+}
+
+/// Lists every instruction with its address, one per line
+pub fn disassemble(program: &[u8]) -> Result<String, VmError> {
+    if program.len() % 2 == 1 {
+        return MissingOperandSnafu { ptr: program.len() - 1 }.fail();
+    }
+    let lines: Vec<String> = program
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| Ok(format!("{:3}: {}", 2 * i, Instr::decode(pair[0], pair[1], 2 * i)?)))
+        .collect::<Result<_, VmError>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Registers and instruction pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    pub regs: [Reg; 3],
+    pub ptr: usize,
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c] = self.regs;
+        write!(f, "ip={:<3} A={a:o} B={b:o} C={c:o}", self.ptr)
+    }
+}
+
+/// Why a run stopped without an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The instruction pointer moved past the end of the program
+    Halted,
+    /// The instruction at this address is a breakpoint
+    Breakpoint(usize),
+}
+
+/// Emulator for the 3-bit computer from 2024 day 17
+#[derive(Debug, Clone)]
+pub struct Vm<'p> {
+    program: &'p [u8],
+    state: State,
+    out: Vec<u8>,
+    steps: usize,
+    limit: Option<usize>,
+    breakpoints: HashSet<usize>,
+    trace: Option<Vec<State>>,
+}
+
+impl<'p> Vm<'p> {
+    pub fn new(program: &'p [u8], regs: [Reg; 3]) -> Self {
+        Self {
+            program,
+            state: State { regs, ptr: 0 },
+            out: Vec::new(),
+            steps: 0,
+            limit: None,
+            breakpoints: HashSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.out
+    }
+
+    pub fn into_output(self) -> Vec<u8> {
+        self.out
+    }
+
+    /// Number of instructions executed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Fails with [`VmError::Limit`] once `limit` instructions have run
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+
+    /// Stops [`Vm::run`] before executing the instruction at `ptr`
+    pub fn add_breakpoint(&mut self, ptr: usize) {
+        self.breakpoints.insert(ptr);
+    }
+
+    pub fn remove_breakpoint(&mut self, ptr: usize) {
+        self.breakpoints.remove(&ptr);
+    }
+
+    /// Records the state after every step from now on
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    /// The recorded states, if tracing is enabled
+    pub fn trace(&self) -> &[State] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// The instruction at the instruction pointer, or `None` once halted
+    pub fn current(&self) -> Result<Option<Instr>, VmError> {
+        let ptr = self.state.ptr;
+        match self.program.get(ptr..ptr + 2) {
+            Some(&[op, operand]) => Instr::decode(op, operand, ptr).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Executes one instruction, returning `false` if already halted
+    pub fn step(&mut self) -> Result<bool, VmError> {
+        let Some(instr) = self.current()? else {
+            return Ok(false);
+        };
+        if let Some(limit) = self.limit {
+            ensure!(self.steps < limit, LimitSnafu { limit });
+        }
+
+        let regs = &mut self.state.regs;
+        let combo = match instr.operand {
+            4..=6 => regs[usize::from(instr.operand) - 4],
+            val => Reg::from(val),
+        };
+        let div = regs[0].checked_shr(combo.try_into().unwrap_or(u32::MAX)).unwrap_or(0);
+        self.state.ptr += 2;
+
+        match instr.op {
+            Opcode::Adv => regs[0] = div,
+            Opcode::Bxl => regs[1] ^= Reg::from(instr.operand),
+            Opcode::Bst => regs[1] = combo % 8,
+            Opcode::Jnz => {
+                if regs[0] != 0 {
+                    self.state.ptr = usize::from(instr.operand);
+                }
+            }
+            Opcode::Bxc => regs[1] ^= regs[2],
+            Opcode::Out => self.out.push((combo % 8) as u8),
+            Opcode::Bdv => regs[1] = div,
+            Opcode::Cdv => regs[2] = div,
+        }
+
+        self.steps += 1;
+        if let Some(trace) = &mut self.trace {
+            trace.push(self.state);
+        }
+        Ok(true)
+    }
+
+    /// Runs until the program halts or reaches a breakpoint. The
+    /// instruction run first is never treated as a breakpoint, so calling
+    /// this again resumes.
+    pub fn run(&mut self) -> Result<Stop, VmError> {
+        if !self.step()? {
+            return Ok(Stop::Halted);
+        }
+        loop {
+            let ptr = self.state.ptr;
+            if self.breakpoints.contains(&ptr) {
+                return Ok(Stop::Breakpoint(ptr));
+            }
+            if !self.step()? {
+                return Ok(Stop::Halted);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum VmError {
+    #[snafu(display("Invalid opcode {op} at {ptr}"))]
+    Opcode { op: u8, ptr: usize },
+    #[snafu(display("Invalid operand {operand} at {ptr}"))]
+    Operand { operand: u8, ptr: usize },
+    #[snafu(display("Missing operand at {ptr}"))]
+    MissingOperand { ptr: usize },
+    #[snafu(display("Exceeded limit of {limit} instructions"))]
+    Limit { limit: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run() {
+        let program = [0, 1, 5, 4, 3, 0];
+        let mut vm = Vm::new(&program, [729, 0, 0]);
+        assert_eq!(vm.run().unwrap(), Stop::Halted);
+        assert_eq!(vm.output(), [4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+        assert_eq!(vm.state(), State { regs: [0, 0, 0], ptr: 6 });
+        assert_eq!(vm.steps(), 30);
+        assert!(!vm.step().unwrap());
+
+        let mut vm = Vm::new(&[2, 6], [0, 0, 9]);
+        vm.run().unwrap();
+        assert_eq!(vm.state().regs[1], 1);
+        let mut vm = Vm::new(&[1, 7], [0, 29, 0]);
+        vm.run().unwrap();
+        assert_eq!(vm.state().regs[1], 26);
+    }
+
+    #[test]
+    fn debugging() {
+        let program = [0, 1, 5, 4, 3, 0];
+        let mut vm = Vm::new(&program, [3, 0, 0]);
+        vm.add_breakpoint(2);
+        vm.enable_trace();
+        assert_eq!(vm.run().unwrap(), Stop::Breakpoint(2));
+        assert_eq!(vm.output(), []);
+        assert_eq!(vm.current().unwrap(), Some(Instr { op: Opcode::Out, operand: 4 }));
+        assert_eq!(vm.run().unwrap(), Stop::Breakpoint(2));
+        assert_eq!(vm.output(), [1]);
+
+        vm.remove_breakpoint(2);
+        assert_eq!(vm.run().unwrap(), Stop::Halted);
+        assert_eq!(vm.output(), [1, 0]);
+        let ptrs: Vec<_> = vm.trace().iter().map(|s| s.ptr).collect();
+        assert_eq!(ptrs, [2, 4, 0, 2, 4, 6]);
+        assert_eq!(vm.trace()[0].to_string(), "ip=2   A=1 B=0 C=0");
+    }
+
+    #[test]
+    fn errors() {
+        let mut vm = Vm::new(&[3, 0], [1, 0, 0]);
+        vm.set_limit(100);
+        assert!(matches!(vm.run(), Err(VmError::Limit { limit: 100 })));
+        assert_eq!(vm.steps(), 100);
+
+        let mut vm = Vm::new(&[0, 1, 8, 0], [1, 0, 0]);
+        assert!(matches!(vm.run(), Err(VmError::Opcode { op: 8, ptr: 2 })));
+        let mut vm = Vm::new(&[5, 7], [1, 0, 0]);
+        assert!(matches!(vm.run(), Err(VmError::Operand { operand: 7, ptr: 0 })));
+        let mut vm = Vm::new(&[1, 8], [1, 0, 0]);
+        assert!(matches!(vm.run(), Err(VmError::Operand { operand: 8, ptr: 0 })));
+    }
+
+    #[test]
+    fn disassembly() {
+        let listing = disassemble(&[2, 4, 1, 7, 7, 5, 4, 0, 5, 5, 0, 3, 3, 0]).unwrap();
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(
+            lines,
+            ["  0: bst A", "  2: bxl 7", "  4: cdv B", "  6: bxc", "  8: out B", " 10: adv 3", " 12: jnz 0"]
+        );
+
+        assert!(matches!(disassemble(&[0, 1, 5]), Err(VmError::MissingOperand { ptr: 2 })));
+        assert!(matches!(disassemble(&[0, 1, 9, 0]), Err(VmError::Opcode { op: 9, ptr: 2 })));
+        assert_eq!(disassemble(&[]).unwrap(), "");
+    }
+}
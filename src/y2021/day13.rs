@@ -1,5 +1,6 @@
 
 use crate::prelude::*;
+use crate::utils::ocr;
 use bitvec::prelude::*;
 use std::ops::{Deref, DerefMut};
 use std::fmt;
@@ -130,7 +131,12 @@ pub fn run() -> GenResult {
         }
     }
 
-    println!("Part 2:\n{}", &paper.shrunk());
+    let paper = paper.shrunk();
+    let height = paper.vec.len() / paper.width;
+    match ocr::read(paper.width, height, |x, y| paper[y * paper.width + x]) {
+        Ok(text) => println!("Part 2: {}", text),
+        Err(e) => println!("Part 2 ({}):\n{}", e, &paper),
+    }
 
     Ok(())
 }
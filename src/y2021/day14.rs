@@ -1,9 +1,8 @@
 
 use crate::prelude::*;
+use crate::utils::memo::Memo;
 use std::ops::{Add, AddAssign};
 use std::iter::Sum;
-use std::sync::Arc;
-use cached::proc_macro::cached;
 
 day!(14);
 
@@ -89,25 +88,29 @@ fn get_output<'a, I: IntoIterator<Item=&'a Template>>(left: u8, right: u8, templ
         .find_map(|t| (t.0 == arr).then(|| t.1))
 }
 
-#[cached]
-fn recurse_solve(left: u8, right: u8, depth: usize, templates: Arc<[Template]>) -> Counts {
-    let mut counts = Counts::default();
-    if let Some(repl) = get_output(left, right, &*templates) {
-        counts.inc(repl);
-        if depth > 0 {
-            counts += &recurse_solve(left, repl, depth - 1, Arc::clone(&templates));
-            counts += &recurse_solve(repl, right, depth - 1, templates);
+/// Counts inserted between a pair after some steps, which only holds for a
+/// single set of templates
+type PairMemo = Memo<(u8, u8, usize), Counts>;
+
+fn recurse_solve(left: u8, right: u8, depth: usize, templates: &[Template], memo: &mut PairMemo) -> Counts {
+    memo.get_or_insert_with((left, right, depth), |memo| {
+        let mut counts = Counts::default();
+        if let Some(repl) = get_output(left, right, templates) {
+            counts.inc(repl);
+            if depth > 0 {
+                counts += &recurse_solve(left, repl, depth - 1, templates, memo);
+                counts += &recurse_solve(repl, right, depth - 1, templates, memo);
+            }
         }
-    }
-    counts
+        counts
+    })
 }
 
-fn solve(info: &Info, steps: usize) -> u64 {
-    let templates = info.templates.as_slice().into();
+fn solve(info: &Info, steps: usize, memo: &mut PairMemo) -> u64 {
     info.polymer
         .iter()
         .tuple_windows()
-        .map(|(&a, &b)| recurse_solve(a, b, steps-1, Arc::clone(&templates)))
+        .map(|(&a, &b)| recurse_solve(a, b, steps-1, &info.templates, memo))
         .sum::<Counts>()
         .value()
 }
@@ -118,10 +121,12 @@ pub fn run() -> GenResult {
                           .parse()
                           .map_err(|_| "parse fail")?;
 
-    let part1 = solve(&info, 10);
+    // Counts for fewer steps are reused by part 2
+    let mut memo = Memo::new();
+    let part1 = solve(&info, 10, &mut memo);
     println!("Part 1: {}", part1);
 
-    let part2 = solve(&info, 40);
+    let part2 = solve(&info, 40, &mut memo);
     println!("Part 2: {}", part2);
 
     Ok(())
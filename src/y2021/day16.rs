@@ -1,103 +1,388 @@
 use crate::prelude::*;
-use std::cmp::Ord;
-use std::ops::{Add, Mul};
-
-use nom::{self, error::Error, Finish, IResult};
+use crate::utils::bits::{from_hex, BitReader, BitWriter, BitsError};
+use std::fmt;
 
 day!(16);
 
 pub fn run() -> GenResult {
-    let input = crate::load_input_string(DAY)?
-        .trim()
-        .chars()
-        .map(|n| n.to_digit(16).unwrap() as u8)
-        .tuples()
-        .map(|(a, b)| (a << 4) + b)
-        .collect_vec();
+    let input = from_hex(crate::load_input_string(DAY)?.trim())?;
+    let packet = Packet::decode(&mut BitReader::new(&input))?;
 
-    let (part1, part2) = parse_transmission(&input).unwrap();
-    println!("Part 1: {}\nPart 2: {}", part1, part2);
+    println!("Part 1: {}\nPart 2: {}", packet.version_sum(), packet.eval()?);
 
     Ok(())
 }
 
-fn parse_transmission(input: &[u8]) -> Result<(u64, u64), Error<&[u8]>> {
-    nom::bits::<_, _, Error<(&[u8], usize)>, _, _>(parse_packet)(input)
-        .finish()
-        .map(|(_, x)| x)
+/// Bits per chunk of a literal value
+const LITERAL_GROUP: u32 = 4;
+
+/// Type ID of literal packets
+const LITERAL_ID: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub version: u8,
+    pub kind: Kind,
+    pub children: Vec<Packet>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    Literal(u64),
+    Greater,
+    Less,
+    Equal,
+}
+
+impl Kind {
+    fn id(self) -> u8 {
+        match self {
+            Kind::Sum => 0,
+            Kind::Product => 1,
+            Kind::Minimum => 2,
+            Kind::Maximum => 3,
+            Kind::Literal(_) => LITERAL_ID,
+            Kind::Greater => 5,
+            Kind::Less => 6,
+            Kind::Equal => 7,
+        }
+    }
+
+    fn operator(id: u8) -> Result<Self, PacketError> {
+        Ok(match id {
+            0 => Kind::Sum,
+            1 => Kind::Product,
+            2 => Kind::Minimum,
+            3 => Kind::Maximum,
+            5 => Kind::Greater,
+            6 => Kind::Less,
+            7 => Kind::Equal,
+            _ => return KindSnafu { id }.fail(),
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Sum => "sum",
+            Kind::Product => "product",
+            Kind::Minimum => "min",
+            Kind::Maximum => "max",
+            Kind::Literal(_) => "literal",
+            Kind::Greater => "gt",
+            Kind::Less => "lt",
+            Kind::Equal => "eq",
+        }
+    }
+
+    /// Operator as written in S-expressions and infix expressions
+    fn symbol(self) -> &'static str {
+        match self {
+            Kind::Sum => "+",
+            Kind::Product => "*",
+            Kind::Minimum => "min",
+            Kind::Maximum => "max",
+            Kind::Literal(_) => "",
+            Kind::Greater => ">",
+            Kind::Less => "<",
+            Kind::Equal => "==",
+        }
+    }
 }
 
-fn parse_packet(input: (&[u8], usize)) -> IResult<(&[u8], usize), (u64, u64)> {
-    let (input, (version, ptype)): (_, (u64, u8)) = nom::sequence::tuple((
-        nom::bits::complete::take(3usize),
-        nom::bits::complete::take(3usize),
-    ))(input)?;
-
-    let (input, (vsn, value)) = if ptype == 4 {
-        let (input, literal) = parse_literal(input)?;
-        (input, (0, literal))
-    } else {
-        let (input, length_type): (_, u8) = nom::bits::complete::take(1usize)(input)?;
-        let (init_fn, fold_fn) = get_operation(ptype);
-        if length_type == 0 {
-            let (input, num_bits): (_, usize) = nom::bits::complete::take(15usize)(input)?;
-            let (sp_input, rst_input) = take_split_bits(input, num_bits);
-            let (_, res) = nom::multi::fold_many1(parse_packet, init_fn, fold_fn)(sp_input)?;
-            (rst_input, res)
+impl Packet {
+    /// Reads one packet and all its sub-packets, leaving any padding unread
+    pub fn decode(bits: &mut BitReader<'_>) -> Result<Self, PacketError> {
+        let version = bits.read(3).context(BitsSnafu)? as u8;
+        let id = bits.read(3).context(BitsSnafu)? as u8;
+
+        if id == LITERAL_ID {
+            let val = bits.read_literal(LITERAL_GROUP).context(BitsSnafu)?;
+            return Ok(Packet { version, kind: Kind::Literal(val), children: Vec::new() });
+        }
+
+        let kind = Kind::operator(id)?;
+        let mut children = Vec::new();
+        if bits.read_bool().context(BitsSnafu)? {
+            let count = bits.read(11).context(BitsSnafu)?;
+            for _ in 0..count {
+                children.push(Packet::decode(bits)?);
+            }
+        } else {
+            let len = bits.read(15).context(BitsSnafu)? as usize;
+            let mut sub = bits.split(len).context(BitsSnafu)?;
+            while !sub.is_empty() {
+                children.push(Packet::decode(&mut sub)?);
+            }
+        }
+
+        Ok(Packet { version, kind, children })
+    }
+
+    /// Writes the packet, giving operators the sub-packet count unless it
+    /// doesn't fit, in which case their total length in bits is used
+    pub fn encode(&self, out: &mut BitWriter) -> Result<(), EncodeError> {
+        ensure!(self.version < 1 << 3, VersionSnafu { version: self.version });
+        out.write(self.version.into(), 3);
+        out.write(self.kind.id().into(), 3);
+
+        if let Kind::Literal(val) = self.kind {
+            out.write_literal(val, LITERAL_GROUP);
+            return Ok(());
+        }
+
+        if self.children.len() < 1 << 11 {
+            out.write_bool(true);
+            out.write(self.children.len() as u64, 11);
+            for child in &self.children {
+                child.encode(out)?;
+            }
         } else {
-            let (input, num_packets): (_, usize) = nom::bits::complete::take(11usize)(input)?;
-            nom::multi::fold_many_m_n(num_packets, num_packets, parse_packet, init_fn, fold_fn)(
-                input,
-            )?
+            let mut sub = BitWriter::new();
+            for child in &self.children {
+                child.encode(&mut sub)?;
+            }
+            ensure!(sub.len() < 1 << 15, TooLongSnafu { count: self.children.len(), bits: sub.len() });
+            out.write_bool(false);
+            out.write(sub.len() as u64, 15);
+            out.append(&sub);
+        }
+        Ok(())
+    }
+
+    /// Iterates over this packet and all its sub-packets, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &Packet> {
+        let mut stack = vec![self];
+        iter::from_fn(move || {
+            let packet = stack.pop()?;
+            stack.extend(packet.children.iter().rev());
+            Some(packet)
+        })
+    }
+
+    /// Sum of the versions of this packet and all its sub-packets
+    pub fn version_sum(&self) -> u64 {
+        self.iter().map(|p| u64::from(p.version)).sum()
+    }
+
+    /// Computes the value of the expression this packet represents
+    pub fn eval(&self) -> Result<u64, EvalError> {
+        let op = self.kind.symbol();
+        let vals: Vec<u64> = self.children.iter().map(Packet::eval).try_collect()?;
+        let compare = |cmp: fn(&u64, &u64) -> bool| match vals[..] {
+            [a, b] => Ok(u64::from(cmp(&a, &b))),
+            _ => AritySnafu { op, count: vals.len() }.fail(),
+        };
+
+        match self.kind {
+            Kind::Literal(val) => Ok(val),
+            Kind::Sum => vals.iter().try_fold(0u64, |acc, &v| acc.checked_add(v)).context(OverflowSnafu { op }),
+            Kind::Product => vals.iter().try_fold(1u64, |acc, &v| acc.checked_mul(v)).context(OverflowSnafu { op }),
+            Kind::Minimum => vals.iter().copied().min().context(EmptySnafu { op }),
+            Kind::Maximum => vals.iter().copied().max().context(EmptySnafu { op }),
+            Kind::Greater => compare(u64::gt),
+            Kind::Less => compare(u64::lt),
+            Kind::Equal => compare(u64::eq),
+        }
+    }
+
+    /// Displays the expression in prefix form, like `(+ 1 (* 2 3))`
+    pub fn sexpr(&self) -> impl fmt::Display + '_ {
+        Sexpr(self)
+    }
+
+    /// Displays the expression in infix form, like `(1 + (2 * 3))`
+    pub fn infix(&self) -> impl fmt::Display + '_ {
+        Infix(self)
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}v{} {}", "", self.version, self.kind.name(), indent = depth * 2)?;
+        if let Kind::Literal(val) = self.kind {
+            write!(f, " {val}")?;
+        }
+        for child in &self.children {
+            writeln!(f)?;
+            child.fmt_indented(f, depth + 1)?;
         }
-    };
+        Ok(())
+    }
+}
+
+/// Displays the packet as a tree, one packet per line
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+struct Sexpr<'p>(&'p Packet);
+
+impl fmt::Display for Sexpr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let packet = self.0;
+        if let Kind::Literal(val) = packet.kind {
+            return write!(f, "{val}");
+        }
+        write!(f, "({}", packet.kind.symbol())?;
+        for child in &packet.children {
+            write!(f, " {}", child.sexpr())?;
+        }
+        f.write_str(")")
+    }
+}
 
-    Ok((input, (version + vsn, value)))
+struct Infix<'p>(&'p Packet);
+
+impl fmt::Display for Infix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let packet = self.0;
+        let children = packet.children.iter().map(Packet::infix);
+        match packet.kind {
+            Kind::Literal(val) => write!(f, "{val}"),
+            Kind::Minimum | Kind::Maximum => {
+                write!(f, "{}({})", packet.kind.symbol(), children.format(", "))
+            }
+            _ if packet.children.len() == 1 => write!(f, "{}", packet.children[0].infix()),
+            kind => {
+                let sep = format!(" {} ", kind.symbol());
+                write!(f, "({})", children.format(&sep))
+            }
+        }
+    }
 }
 
-fn get_operation(
-    ptype: u8,
-) -> (
-    impl FnMut() -> (u64, u64),
-    impl FnMut((u64, u64), (u64, u64)) -> (u64, u64),
-) {
-    let (init, fold_fn_inner): (u64, fn(u64, u64) -> u64) = match ptype {
-        0 => (0, Add::add),
-        1 => (1, Mul::mul),
-        2 => (u64::MAX, Ord::min),
-        3 => (0, Ord::max),
-        5 => (u64::MAX, |acc, v| if acc == u64::MAX { v } else if acc > v { 1 } else { 0 }),
-        6 => (u64::MAX, |acc, v| if acc == u64::MAX { v } else if acc < v { 1 } else { 0 }),
-        7 => (u64::MAX, |acc, v| if acc == u64::MAX { v } else if acc == v { 1 } else { 0 }),
-        _ => panic!("Invalid packet type: {}", ptype),
-    };
-
-    let fold_fn =
-        move |acc: (u64, u64), val: (u64, u64)| (acc.0 + val.0, fold_fn_inner(acc.1, val.1));
-    let init_fn = move || (0u64, init);
-
-    (init_fn, fold_fn)
+#[derive(Debug, Snafu)]
+pub enum EvalError {
+    #[snafu(display("Overflow evaluating `{op}`"))]
+    Overflow { op: &'static str },
+    #[snafu(display("`{op}` needs exactly 2 operands but has {count}"))]
+    Arity { op: &'static str, count: usize },
+    #[snafu(display("`{op}` has no operands"))]
+    Empty { op: &'static str },
 }
 
-fn take_split_bits(input: (&[u8], usize), count: usize) -> ((&[u8], usize), (&[u8], usize)) {
-    let bit_pos = input.1 + count;
-    let (byte_offset, bit_offset) = (bit_pos / 8, bit_pos % 8);
-    (
-        (&input.0[0..=byte_offset], input.1),
-        (&input.0[byte_offset..], bit_offset),
-    )
+#[derive(Debug, Snafu)]
+pub enum EncodeError {
+    #[snafu(display("Version {version} doesn't fit in 3 bits"))]
+    Version { version: u8 },
+    #[snafu(display("{count} sub-packets take {bits} bits, too many to count or measure"))]
+    TooLong { count: usize, bits: usize },
 }
 
-fn parse_literal(mut input: (&[u8], usize)) -> IResult<(&[u8], usize), u64> {
-    let mut literal = 0u64;
-    loop {
-        let (input2, cont): (_, u8) = nom::bits::complete::take(1usize)(input)?;
-        let (input3, bits): (_, u64) = nom::bits::complete::take(4usize)(input2)?;
-        input = input3;
-        literal <<= 4;
-        literal |= bits;
-        if cont == 0 {
-            return Ok((input3, literal));
+#[derive(Debug, Snafu)]
+pub enum PacketError {
+    #[snafu(display("Truncated or malformed packet"))]
+    Bits { source: BitsError },
+    #[snafu(display("Unknown packet type ID {id}"))]
+    Kind { id: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Packet {
+        Packet::decode(&mut BitReader::new(bytes)).unwrap()
+    }
+
+    fn encode(packet: &Packet) -> Result<Vec<u8>, EncodeError> {
+        let mut out = BitWriter::new();
+        packet.encode(&mut out)?;
+        Ok(out.into_bytes())
+    }
+
+    fn literal(val: u64) -> Packet {
+        Packet { version: 1, kind: Kind::Literal(val), children: Vec::new() }
+    }
+
+    fn op(kind: Kind, children: Vec<Packet>) -> Packet {
+        Packet { version: 1, kind, children }
+    }
+
+    #[test]
+    fn eval() {
+        let cases = [
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ];
+        for (hex, val) in cases {
+            assert_eq!(decode(&from_hex(hex).unwrap()).eval().unwrap(), val, "{hex}");
+        }
+
+        for (hex, sum) in [("8A004A801A8002F478", 16), ("620080001611562C8802118E34", 12)] {
+            assert_eq!(decode(&from_hex(hex).unwrap()).version_sum(), sum, "{hex}");
+        }
+    }
+
+    #[test]
+    fn eval_errors() {
+        let sum = op(Kind::Sum, vec![literal(u64::MAX), literal(1)]);
+        assert!(matches!(sum.eval(), Err(EvalError::Overflow { op: "+" })));
+        let product = op(Kind::Product, vec![literal(1 << 32), literal(1 << 32)]);
+        assert!(matches!(product.eval(), Err(EvalError::Overflow { op: "*" })));
+
+        let gt = op(Kind::Greater, vec![literal(1)]);
+        assert!(matches!(gt.eval(), Err(EvalError::Arity { op: ">", count: 1 })));
+        let eq = op(Kind::Equal, vec![literal(1), literal(1), literal(1)]);
+        assert!(matches!(eq.eval(), Err(EvalError::Arity { op: "==", count: 3 })));
+
+        let min = op(Kind::Minimum, Vec::new());
+        assert!(matches!(min.eval(), Err(EvalError::Empty { op: "min" })));
+        assert_eq!(op(Kind::Sum, Vec::new()).eval().unwrap(), 0);
+
+        // Errors in sub-packets are passed up
+        let nested = op(Kind::Maximum, vec![literal(2), op(Kind::Less, Vec::new())]);
+        assert!(matches!(nested.eval(), Err(EvalError::Arity { op: "<", count: 0 })));
+    }
+
+    #[test]
+    fn render() {
+        let packet = op(Kind::Sum, vec![literal(1), op(Kind::Product, vec![literal(2), literal(3)])]);
+        assert_eq!(packet.sexpr().to_string(), "(+ 1 (* 2 3))");
+        assert_eq!(packet.infix().to_string(), "(1 + (2 * 3))");
+
+        let packet = op(Kind::Minimum, vec![literal(4), op(Kind::Sum, vec![literal(5)])]);
+        assert_eq!(packet.sexpr().to_string(), "(min 4 (+ 5))");
+        assert_eq!(packet.infix().to_string(), "min(4, 5)");
+
+        let packet = decode(&from_hex("9C0141080250320F1802104A08").unwrap());
+        assert_eq!(packet.sexpr().to_string(), "(== (+ 1 3) (* 2 2))");
+        assert_eq!(packet.infix().to_string(), "((1 + 3) == (2 * 2))");
+
+        let packet = op(Kind::Greater, vec![literal(7), literal(9)]);
+        assert_eq!(packet.to_string(), "v1 gt\n  v1 literal 7\n  v1 literal 9");
+    }
+
+    #[test]
+    fn round_trip() {
+        for hex in ["D2FE28", "38006F45291200", "EE00D40C823060", "9C0141080250320F1802104A08"] {
+            let packet = decode(&from_hex(hex).unwrap());
+            let reencoded = decode(&encode(&packet).unwrap());
+            assert_eq!(reencoded, packet, "{hex}");
         }
     }
+
+    #[test]
+    fn many_children() {
+        // Too many sub-packets to count, so their length is used instead,
+        // at 11 bits per literal
+        let packet = Packet { version: 2, kind: Kind::Sum, children: vec![literal(3); 2500] };
+        assert_eq!(decode(&encode(&packet).unwrap()), packet);
+
+        // Too long to fit in the 15 bit length either
+        let packet = Packet { children: vec![literal(3); 3000], ..packet };
+        assert!(matches!(encode(&packet), Err(EncodeError::TooLong { count: 3000, bits: 33000 })));
+
+        assert!(matches!(encode(&Packet { version: 8, ..literal(1) }), Err(EncodeError::Version { version: 8 })));
+    }
 }
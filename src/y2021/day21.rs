@@ -1,6 +1,5 @@
 
-use crate::prelude::*;
-use cached::proc_macro::cached;
+use crate::{prelude::*, utils::memo::Memo};
 
 day!(21);
 
@@ -17,9 +16,10 @@ pub fn run() -> GenResult {
     println!("Part 1: {}", part1);
 
 	let start = std::time::Instant::now();
-    let part2 = count_universes(player1, player2);
+    let mut memo = Memo::new();
+    let part2 = count_universes(player1, player2, &mut memo);
 	let dur = start.elapsed();
-    println!("Part 2: {} ({:?}) ({} iters)", part2, dur, COUNT.load(Ordering::Relaxed));
+    println!("Part 2: {} ({:?}) ({})", part2, dur, memo.stats());
 
     Ok(())
 }
@@ -39,40 +39,41 @@ fn count_rolls(mut ply1: Player, mut ply2: Player) -> u64 {
     }
 }
 
-fn count_universes(ply1: Player, ply2: Player) -> u64 {
-    let (a, b) = inner_unis2(ply1, ply2, true);
+/// Wins for each player from a game state, keyed by both players and
+/// whether it is player 1's turn
+type UniMemo = Memo<(Player, Player, bool), (u64, u64)>;
+
+fn count_universes(ply1: Player, ply2: Player, memo: &mut UniMemo) -> u64 {
+    let (a, b) = inner_unis2(ply1, ply2, true, memo);
     a.max(b)
 }
 
-use std::sync::atomic::{AtomicU64, Ordering};
-static COUNT: AtomicU64 = AtomicU64::new(0);
-
-#[cached]
-fn inner_unis2(ply1: Player, ply2: Player, turn: bool) -> (u64, u64) {
-	COUNT.fetch_add(UNIS.len() as u64, Ordering::Relaxed);
-    UNIS.iter()
-        .map(move |&(n, c)| {
-            if turn {
-                // Player 1
-                let ply1 = ply1.turn3(n);
-                if ply1.score >= PT_CAP {
-                    (c, 0)
-                } else {
-                    let r1 = inner_unis2(ply1, ply2, false);
-                    (c * r1.0, c * r1.1)
-                }
-            } else {
-                // Player 2
-                let ply2 = ply2.turn3(n);
-                if ply2.score >= PT_CAP {
-                    (0, c)
+fn inner_unis2(ply1: Player, ply2: Player, turn: bool, memo: &mut UniMemo) -> (u64, u64) {
+    memo.get_or_insert_with((ply1, ply2, turn), |memo| {
+        UNIS.iter()
+            .map(|&(n, c)| {
+                if turn {
+                    // Player 1
+                    let ply1 = ply1.turn3(n);
+                    if ply1.score >= PT_CAP {
+                        (c, 0)
+                    } else {
+                        let r1 = inner_unis2(ply1, ply2, false, memo);
+                        (c * r1.0, c * r1.1)
+                    }
                 } else {
-                    let r2 = inner_unis2(ply1, ply2, true);
-                    (c * r2.0, c * r2.1)
+                    // Player 2
+                    let ply2 = ply2.turn3(n);
+                    if ply2.score >= PT_CAP {
+                        (0, c)
+                    } else {
+                        let r2 = inner_unis2(ply1, ply2, true, memo);
+                        (c * r2.0, c * r2.1)
+                    }
                 }
-            }
-        })
-        .fold((0, 0), |acc, v| (acc.0 + v.0, acc.1 + v.1))
+            })
+            .fold((0, 0), |acc, v| (acc.0 + v.0, acc.1 + v.1))
+    })
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -1,10 +1,11 @@
 use std::num::ParseIntError;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::interval::Interval};
 
 day!(4);
 
-type Section = std::ops::RangeInclusive<u8>;
+/// Sections are numbered with `u8`s, so the exclusive end needs a wider type
+type Section = Interval<u16>;
 
 #[derive(Debug, Snafu)]
 enum LineParseError {
@@ -21,33 +22,20 @@ fn parse_line(line: &str) -> Result<(Section, Section), LineParseError> {
 
     fn parse_section(s: &str) -> Result<Section, LineParseError> {
         let (start, end) = s.split_once('-').ok_or(LineParseError::MissingHyphen)?;
-        Ok(Section::new(
-            start.parse().context(IntParseSnafu)?,
-            end.parse().context(IntParseSnafu)?,
-        ))
+        let start: u8 = start.parse().context(IntParseSnafu)?;
+        let end: u8 = end.parse().context(IntParseSnafu)?;
+        Ok(Section::new(start.into(), u16::from(end) + 1))
     }
 
     Ok((parse_section(one)?, parse_section(two)?))
 }
 
 fn sections_contained(a: &Section, b: &Section) -> bool {
-    let [small, big] = {
-        let mut arr = [a, b];
-        arr.sort_by_key(|s| s.len());
-        arr
-    };
-
-    (big.start() <= small.start()) && (big.end() >= small.end())
+    a.contains_interval(b) || b.contains_interval(a)
 }
 
 fn sections_overlap(a: &Section, b: &Section) -> bool {
-    let [small, big] = {
-        let mut arr = [a, b];
-        arr.sort_by_key(|s| s.len());
-        arr
-    };
-
-    big.contains(small.start()) || big.contains(small.end())
+    a.overlaps(b)
 }
 
 pub fn run() -> Result<(), Whatever> {
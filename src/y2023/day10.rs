@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::{render, sgrid}};
 
 day!(10);
 
@@ -15,6 +15,10 @@ pub fn run() -> Result<(), Whatever> {
     let part2 = enclosed(&grid, &mut dists);
     println!("Part 2: {part2}");
 
+    if crate::has_arg("render") {
+        println!("{}", show_loop(&grid, &dists));
+    }
+
     Ok(())
 }
 
@@ -85,6 +89,20 @@ fn enclosed(grid: &Grid, dists: &mut [State]) -> u64 {
     count
 }
 
+/// Displays the loop as box drawing characters, with the tiles it
+/// encloses highlighted
+fn show_loop(grid: &Grid, states: &[State]) -> String {
+    let cells = grid.nodes.iter().copied().zip(states.iter().cloned()).collect();
+    let cells = sgrid::Grid::new(cells, grid.width);
+    render::ansi(&cells, |(tile, state)| match state {
+        State { dist: Some(_), .. } => (tile.as_char(), [255, 255, 255]),
+        // Left unvisited by `enclosed`
+        State { visited: false, .. } => ('\u{2588}', [0, 200, 0]),
+        _ => ('.', [96, 96, 96]),
+    })
+    .to_string()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Tile {
     Ground,
@@ -100,6 +118,19 @@ enum Tile {
 type Coord = [isize; 2];
 
 impl Tile {
+    pub const fn as_char(&self) -> char {
+        match self {
+            Tile::Ground => '.',
+            Tile::Start => 'S',
+            Tile::NorthSouth => '\u{2502}',
+            Tile::WestEast => '\u{2500}',
+            Tile::NorthEast => '\u{2514}',
+            Tile::NorthWest => '\u{2518}',
+            Tile::SouthWest => '\u{2510}',
+            Tile::SouthEast => '\u{250C}',
+        }
+    }
+
     pub const fn conns(&self) -> &'static [Coord] {
         const N: Coord = [0, -1];
         const S: Coord = [0, 1];
@@ -1,5 +1,5 @@
 
-use crate::{prelude::*, utils::sgrid::{GridParseErr, Grid}};
+use crate::{prelude::*, utils::{parser::paragraphs, sgrid::{GridParseErr, Grid}}};
 
 day!(13);
 
@@ -71,22 +71,15 @@ fn solve(grid: &Grid<bool>, smudges: usize) -> usize {
     0
 }
 
-fn each_grid<F: FnMut(Grid<bool>)>(mut input: &[u8], mut f: F) -> Result<(), GridParseErr> {
-    let line_ending = nom::character::complete::line_ending::<&[u8], ()>;
-
+fn each_grid<F: FnMut(Grid<bool>)>(input: &[u8], mut f: F) -> Result<(), GridParseErr> {
     let parse_elem = |b| match b {
         b'#' => Some(true),
         b'.' => Some(false),
         _ => None,
     };
 
-    while !input.is_empty() {
-        if let Ok((inp, _)) = line_ending(input) {
-            input = inp;
-        } 
-
-        let grid;
-        (input, grid) = Grid::parse(input, parse_elem)?;
+    for block in paragraphs(input) {
+        let (_, grid) = Grid::parse(block, parse_elem)?;
         f(grid);
     }
 
@@ -1,8 +1,6 @@
 use std::{num::Wrapping, fmt::{Display, Write}};
 
-use indexmap::IndexSet;
-
-use crate::prelude::*;
+use crate::{prelude::*, utils::{cycle, record::Recorder, render::Rgb}};
 
 day!(14);
 
@@ -53,34 +51,29 @@ fn part1(grid: &Grid) -> usize {
 }
 
 fn part2(grid: &Grid) -> usize {
-    const GOAL: usize = 4_000_000_000;
-
-    let mut cache = IndexSet::<Box<[Tile]>>::new();
-    cache.insert(grid.clone().into_inner().into_boxed_slice());
-
-    let mut cur = grid.rotate_left();
-
-    for cur_idx in 1..=GOAL {
-        shift_left(&mut cur);
-        if cur_idx == GOAL { break }
-
-        let next = cur.rotate_right();
-        let (match_idx, inserted) = cache.insert_full(cur.into_inner().into_boxed_slice());
-
-        if !inserted {
-            let cycle_len = cur_idx - match_idx;
-            println!("Cycle detected from {match_idx} to {cur_idx}  (length {cycle_len})");
-            let final_idx = (GOAL - match_idx) % cycle_len + match_idx;
-            let final_elems = cache.swap_remove_index(final_idx).expect("Missing final index");
-            let final_grid = Grid::new(final_elems.into_vec(), grid.height()).rotate_right();
-            cur = final_grid;
-            break;
+    const SPINS: usize = 1_000_000_000;
+
+    // Keep north to the left, so each tilt is a shift left followed by a
+    // turn to put the next direction on the left
+    let start = grid.rotate_left();
+    let width = start.width();
+    let mut rec = Recorder::from_args(4);
+    let end = cycle::nth_state(start.into_inner().into_boxed_slice(), SPINS, |tiles| {
+        let mut cur = Grid::new(tiles.to_vec(), width);
+        for _ in 0..4 {
+            shift_left(&mut cur);
+            cur = cur.rotate_right();
         }
+        if let Some(rec) = &mut rec {
+            rec.snapshot(&cur.rotate_right(), Tile::color);
+        }
+        cur.into_inner().into_boxed_slice()
+    });
 
-        cur = next;
+    if let Some(Err(e)) = rec.map(|rec| rec.finish("y2023_day14")) {
+        eprintln!("Failed to save recording: {e}");
     }
-
-    score_grid(&cur)
+    score_grid(&Grid::new(end.into_vec(), width))
 }
 
 fn score_grid(grid: &Grid) -> usize {
@@ -130,6 +123,16 @@ enum Tile {
     Empty,
 }
 
+impl Tile {
+    fn color(self) -> Rgb {
+        match self {
+            Tile::Round => [220, 220, 220],
+            Tile::Cube => [100, 100, 100],
+            Tile::Empty => [0, 0, 0],
+        }
+    }
+}
+
 impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let c = match *self {
@@ -1,6 +1,6 @@
 use bitvec::BitArr;
 
-use crate::{prelude::*, utils::sgrid::Dir};
+use crate::{prelude::*, utils::{render::{self, Rgb}, sgrid::Dir}};
 
 day!(16);
 
@@ -12,6 +12,12 @@ pub fn run() -> Result<(), Whatever> {
     println!("Parsed grid of size {}x{}", grid.width(), grid.height());
 
     println!("Part 1: {}", part1(&mut grid));
+
+    if crate::has_arg("render") {
+        const PATH: &str = "y2023_day16.png";
+        whatever!(render::save_image(&grid, 4, Tile::color, PATH), "Failed to render beams");
+        println!("Saved energized tiles to {PATH}");
+    }
     println!("Part 2: {}", part2(&mut grid));
 
     Ok(())
@@ -141,11 +147,24 @@ fn parse_elem(b: u8) -> Option<Tile> {
     })
 }
 
+#[derive(Clone)]
 struct Tile {
     kind: Kind,
     power: Power,
 }
 
+impl Tile {
+    /// Energized tiles are lit, with mirrors and splitters standing out
+    fn color(self) -> Rgb {
+        match (self.kind, self.power.any_set()) {
+            (Kind::Empty, false) => [0, 0, 0],
+            (Kind::Empty, true) => [255, 200, 0],
+            (_, false) => [96, 96, 96],
+            (_, true) => [255, 255, 255],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Kind {
     Empty,
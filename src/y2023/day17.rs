@@ -1,8 +1,4 @@
-use std::{cmp::Ordering, collections::BinaryHeap};
-
-use bitvec::BitArr;
-
-use crate::prelude::*;
+use crate::{prelude::*, utils::search};
 
 day!(17);
 
@@ -25,143 +21,41 @@ struct Grid<T> {
     nodes: Box<[T]>,
 }
 
-#[derive(Debug, Clone, Default)]
-struct DState {
-    dist: Option<u64>,
-    steps: BitArr!(for 64),
-}
-
-#[derive(Debug, Clone, Default)]
-struct PerDir<T> {
-    north: T,
-    south: T,
-    east: T,
-    west: T,
-}
-
-impl<T> std::ops::Index<Dir> for PerDir<T> {
-    type Output = T;
-
-    fn index(&self, dir: Dir) -> &T {
-        match dir {
-            Dir::North => &self.north,
-            Dir::South => &self.south,
-            Dir::East => &self.east,
-            Dir::West => &self.west,
-        }
-    }
-}
-
-impl<T> std::ops::IndexMut<Dir> for PerDir<T> {
-    fn index_mut(&mut self, dir: Dir) -> &mut T {
-        match dir {
-            Dir::North => &mut self.north,
-            Dir::South => &mut self.south,
-            Dir::East => &mut self.east,
-            Dir::West => &mut self.west,
-        }
-    }
-}
-
 impl Grid<u8> {
     pub fn find_path(&self, min_steps: u8, max_steps: u8) -> u64 {
         if self.nodes.is_empty() {
             return 0;
         }
 
-        let nodes = &self.nodes[..];
-
-        let mut dists: Box<[PerDir<DState>]> =
-            vec![Default::default(); self.nodes.len()].into_boxed_slice();
-
-        {
-            let lastd = dists.last_mut().unwrap();
-            lastd.north = Default::default();
-            lastd.west = Default::default();
-        }
-
-        let mut heap = BinaryHeap::new();
-        let last_pos = dists.len() - 1;
-        heap.push(State {
-            position: last_pos,
-            cost: 0,
-            extra: DirState {
-                dir: Dir::North,
-                steps: 0,
-            },
-        });
-
-        heap.push(State {
-            position: last_pos,
-            cost: 0,
-            extra: DirState {
-                dir: Dir::West,
-                steps: 0,
+        let last_pos = self.nodes.len() - 1;
+        let starts = [Dir::East, Dir::South].map(|dir| (0, DirState { dir, steps: 0 }));
+
+        let paths = search::dijkstra(
+            starts,
+            |&(position, extra)| {
+                Dir::ALL.into_iter().filter_map(move |adj_dir| {
+                    let extra = extra.adv_dir(adj_dir, min_steps, max_steps)?;
+                    let adj = self.step(position, adj_dir)?;
+                    Some(((adj, extra), u64::from(self.nodes[adj])))
+                })
             },
-        });
-
-        while let Some(State {
-            cost,
-            position,
-            extra,
-        }) = heap.pop()
-        {
-            let next_cost = cost.saturating_add(u64::from(nodes[position]));
-
-            // Check adjacencies
-            let mut check_adj = |adj: usize, adj_dir: Dir| {
-                if let Some(extra) = extra.adv_dir(adj_dir, min_steps, max_steps) {
-                    let dstate = &mut dists[adj][adj_dir];
-                    let stepn = usize::from(extra.steps);
-                    if !dstate.steps[stepn] {
-                        dstate.steps.set(stepn, true);
-
-                        if extra.steps >= min_steps {
-                            dstate.dist = Some(match dstate.dist {
-                                Some(d) => d.min(next_cost),
-                                None => next_cost,
-                            });
-                        }
+            |&(position, extra)| position == last_pos && extra.steps >= min_steps,
+        );
 
-                        let next = State {
-                            cost: next_cost,
-                            position: adj,
-                            extra,
-                        };
-
-                        heap.push(next);
-                    }
-                }
-            };
-
-            // Check north
-            if let Some(next_pos) = position.checked_sub(self.width) {
-                check_adj(next_pos, Dir::North);
-            }
+        paths.goal_dist().expect("Target node was never reached")
+    }
 
-            // Check south
-            if let Some(next_pos) = position
+    /// Index of the node next to `position` in `dir`
+    fn step(&self, position: usize, dir: Dir) -> Option<usize> {
+        let rem = position % self.width;
+        match dir {
+            Dir::North => position.checked_sub(self.width),
+            Dir::South => position
                 .checked_add(self.width)
-                .filter(|&x| x < nodes.len())
-            {
-                check_adj(next_pos, Dir::South);
-            }
-
-            let rem = position % self.width;
-
-            // Check east
-            if rem + 1 < self.width {
-                check_adj(position + 1, Dir::East);
-            }
-
-            // Check west
-            if rem > 0 {
-                check_adj(position - 1, Dir::West);
-            }
+                .filter(|&x| x < self.nodes.len()),
+            Dir::East => (rem + 1 < self.width).then_some(position + 1),
+            Dir::West => (rem > 0).then(|| position - 1),
         }
-
-        let min = Dir::ALL.iter().filter_map(|&d| dists[0][d].dist).min();
-        min.expect("Target node was never reached")
     }
 
     fn parse(input: &[u8]) -> Result<Self, BadChar> {
@@ -198,7 +92,7 @@ struct BadChar {
     val: u8,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 enum Dir {
     North,
     South,
@@ -219,6 +113,7 @@ impl Dir {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct DirState {
     dir: Dir,
     steps: u8,
@@ -238,32 +133,3 @@ impl DirState {
         }
     }
 }
-
-struct State<E> {
-    cost: u64,
-    position: usize,
-    extra: E,
-}
-
-impl<E> Eq for State<E> {}
-
-impl<E> PartialEq for State<E> {
-    fn eq(&self, other: &Self) -> bool {
-        self.cost == other.cost && self.position == other.position
-    }
-}
-
-impl<E> Ord for State<E> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
-}
-
-impl<E> PartialOrd for State<E> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
@@ -1,7 +1,9 @@
 use indexmap::IndexMap;
 use smallvec::SmallVec;
 
-use crate::{prelude::*, utils::NomFail};
+use std::fmt;
+
+use crate::{prelude::*, utils::{dot::Dot, interval::{Cuboid, Interval}, NomFail}};
 
 day!(19);
 
@@ -9,100 +11,181 @@ pub fn run() -> Result<(), Whatever> {
     let input = whatever!(load_input_bytes(DAY), "Failed to load input");
     let (_, system) = whatever!(parser::system(&input).finish().map_err(NomFail::from), "Failed to parse input");
 
-    let part1 = solve(&system);
+    if crate::has_arg("dot") {
+        println!("{}", system.to_dot());
+    }
+
+    let compiled = whatever!(system.compile(), "Failed to compile workflows");
+    if crate::has_arg("analyze") {
+        compiled.report(&system);
+    }
+
+    let part1 = solve(&system, &compiled.tree);
     println!("Part 1: {part1}");
 
-    let part2 = count_states(&system);
+    let part2: u64 = compiled.tree.accepted().iter().map(PartRanges::volume).sum();
     println!("Part 2: {part2}");
 
     Ok(())
 }
 
-fn count_states(system: &System<'_>) -> u64 {
-    let mut count = 0u64;
-    let init = PartRanges::new(1..4001);
-    let start = system.flows.get_index_of(&b"in"[..]).unwrap();
-    let mut stack = vec![(init, start)];
-
-    while let Some((mut ranges, label)) = stack.pop() {
-        let cur = system.flows[label].as_ref().unwrap();
-        for rule in &cur.conds {
-            let pass;
-            [pass, ranges] = ranges.split(&rule.comp);
-
-            match rule.insn {
-                Insn::Reject => (),
-                Insn::Accept => count += pass.num_states(),
-                Insn::Goto(idx) => stack.push((pass, idx)),
-            }
-        }
+/// Workflows compiled into a single decision tree
+#[derive(Debug)]
+struct Compiled {
+    tree: Tree,
+    /// Rules which no part reaching them can match, as workflow and rule
+    /// index, with the fallback rule after the conditions
+    dead: Vec<(usize, usize)>,
+    /// Workflows which no part can reach
+    unreachable: Vec<usize>,
+}
 
-        match cur.term {
-            Insn::Reject => (),
-            Insn::Accept => count += ranges.num_states(),
-            Insn::Goto(idx) => stack.push((ranges, idx)),
+impl Compiled {
+    fn report(&self, system: &System<'_>) {
+        let name = |idx| String::from_utf8_lossy(system.flows.get_index(idx).unwrap().0);
+        println!("Decision tree has {} comparisons", self.tree.size());
+        for &idx in &self.unreachable {
+            println!("Unreachable workflow {}", name(idx));
+        }
+        for &(idx, rule) in &self.dead {
+            let flow = system.flows[idx].as_ref().unwrap();
+            match flow.conds.get(rule) {
+                Some(rule) => println!("Dead rule in {}: {}", name(idx), rule.comp),
+                None => println!("Dead fallback in {}", name(idx)),
+            }
         }
     }
+}
 
-    count
+/// Whether each part is accepted, with every comparison needed to decide
+/// and none which always pass or fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tree {
+    Accept,
+    Reject,
+    Branch {
+        comp: Compare,
+        pass: Box<Tree>,
+        fail: Box<Tree>,
+    },
 }
 
-type Range = std::ops::Range<u16>;
+impl Tree {
+    /// Joins two subtrees, skipping the comparison if both sides agree
+    fn branch(comp: Compare, pass: Tree, fail: Tree) -> Self {
+        if pass == fail {
+            return pass;
+        }
+        Tree::Branch { comp, pass: Box::new(pass), fail: Box::new(fail) }
+    }
 
-#[derive(Debug, Clone)]
-struct PartRanges {
-    x: Range,
-    m: Range,
-    a: Range,
-    s: Range,
-}
+    fn accepts(&self, part: &Part) -> bool {
+        let mut cur = self;
+        loop {
+            match cur {
+                Tree::Accept => return true,
+                Tree::Reject => return false,
+                Tree::Branch { comp, pass, fail } => cur = if comp.check(part) { pass } else { fail },
+            }
+        }
+    }
 
-impl PartRanges {
-    pub fn new(range: Range) -> Self {
-        Self {
-            x: range.clone(),
-            m: range.clone(),
-            a: range.clone(),
-            s: range,
+    /// Every disjoint box of ratings which is accepted
+    fn accepted(&self) -> Vec<PartRanges> {
+        let mut boxes = Vec::new();
+        let mut stack = vec![(self, ALL_PARTS)];
+        while let Some((tree, ranges)) = stack.pop() {
+            match tree {
+                Tree::Accept => boxes.push(ranges),
+                Tree::Reject => (),
+                Tree::Branch { comp, pass, fail } => {
+                    let [pranges, franges] = comp.split(&ranges);
+                    stack.push((pass, pranges));
+                    stack.push((fail, franges));
+                }
+            }
         }
+        boxes
     }
 
-    pub fn num_states(&self) -> u64 {
-        [&self.x, &self.m, &self.a, &self.s]
-            .iter()
-            .map(|r| u64::from(r.end - r.start))
-            .product()
+    /// Number of comparisons in the tree
+    fn size(&self) -> usize {
+        match self {
+            Tree::Accept | Tree::Reject => 0,
+            Tree::Branch { pass, fail, .. } => 1 + pass.size() + fail.size(),
+        }
     }
+}
 
-    pub fn split(&self, cond: &Compare) -> [Self; 2] {
-        let mut ranges = [self.clone(), self.clone()];
-        let [pass, fail] = &mut ranges;
-    
-        let (pass, fail) = match cond.category {
-            Category::Cool => (&mut pass.x, &mut fail.x),
-            Category::Music => (&mut pass.m, &mut fail.m),
-            Category::Aero => (&mut pass.a, &mut fail.a),
-            Category::Shiny => (&mut pass.s, &mut fail.s),
+/// Inlines workflows into a [`Tree`], tracking the ratings which can reach
+/// each rule so that rules which always or never match are dropped
+struct Compiler<'s, 'l> {
+    system: &'s System<'l>,
+    /// Whether each rule of each workflow matched any ratings
+    live: Vec<Vec<bool>>,
+    /// Workflows being compiled, to catch loops
+    stack: Vec<usize>,
+}
+
+impl Compiler<'_, '_> {
+    fn insn(&mut self, insn: Insn, ranges: PartRanges) -> Result<Tree, CompileError> {
+        match insn {
+            Insn::Accept => Ok(Tree::Accept),
+            Insn::Reject => Ok(Tree::Reject),
+            Insn::Goto(idx) => {
+                ensure!(!self.stack.contains(&idx), LoopSnafu { label: self.system.name(idx) });
+                self.stack.push(idx);
+                let tree = self.rules(idx, 0, ranges)?;
+                self.stack.pop();
+                Ok(tree)
+            }
+        }
+    }
+
+    fn rules(&mut self, idx: usize, rule: usize, ranges: PartRanges) -> Result<Tree, CompileError> {
+        let system = self.system;
+        let flow = system.flows[idx].as_ref().expect("Workflows checked before compiling");
+        let Some(cond) = flow.conds.get(rule) else {
+            self.live[idx][rule] = true;
+            return self.insn(flow.term, ranges);
         };
 
-        if cond.greater {
-            let amt = cond.amt + 1;
-            pass.start = pass.start.max(amt);
-            fail.end = fail.end.min(amt);
-        } else {
-            pass.end = pass.end.min(cond.amt);
-            fail.start = fail.start.max(cond.amt);
+        let [pass, fail] = cond.comp.split(&ranges);
+        if pass.is_empty() {
+            return self.rules(idx, rule + 1, fail);
+        }
+        self.live[idx][rule] = true;
+        if fail.is_empty() {
+            return self.insn(cond.insn, pass);
         }
 
-        ranges
+        let pass = self.insn(cond.insn, pass)?;
+        let fail = self.rules(idx, rule + 1, fail)?;
+        Ok(Tree::branch(cond.comp, pass, fail))
     }
 }
 
-fn solve(system: &System<'_>) -> u64 {
+#[derive(Debug, Snafu)]
+enum CompileError {
+    #[snafu(display("No start workflow `in`"))]
+    Start,
+    #[snafu(display("Workflow `{label}` is used but never defined"))]
+    Undefined { label: String },
+    #[snafu(display("Workflow `{label}` can send parts back to itself"))]
+    Loop { label: String },
+}
+
+/// Ranges of each rating, with an axis per [`Category`]
+type PartRanges = Cuboid<u16, 4>;
+
+/// Every possible part, with ratings from 1 to 4000
+const ALL_PARTS: PartRanges = Cuboid::new([Interval::new(1, 4001); 4]);
+
+fn solve(system: &System<'_>, tree: &Tree) -> u64 {
     system
         .parts
         .iter()
-        .filter(|p| system.validate(p))
+        .filter(|p| tree.accepts(p))
         .map(|p| u64::from(p.total()))
         .sum()
 }
@@ -143,7 +226,7 @@ enum Insn {
     Reject,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct Compare {
     category: Category,
     greater: bool,
@@ -165,8 +248,39 @@ impl Compare {
             cat < self.amt
         }
     }
+
+    /// Splits `ranges` into the parts which pass and fail the comparison
+    pub fn split(&self, ranges: &PartRanges) -> [PartRanges; 2] {
+        let axis = self.category as usize;
+        if self.greater {
+            let Some(above) = self.amt.checked_add(1) else {
+                // No rating is greater than the largest value
+                let mut none = *ranges;
+                none.axes[axis] = Interval::new(0, 0);
+                return [none, *ranges];
+            };
+            let [fail, pass] = ranges.split_at(axis, above);
+            [pass, fail]
+        } else {
+            ranges.split_at(axis, self.amt)
+        }
+    }
+}
+
+impl fmt::Display for Compare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cat = match self.category {
+            Category::Cool => 'x',
+            Category::Music => 'm',
+            Category::Aero => 'a',
+            Category::Shiny => 's',
+        };
+        let op = if self.greater { '>' } else { '<' };
+        write!(f, "{cat}{op}{}", self.amt)
+    }
 }
 
+/// Rating categories, in the order of [`PartRanges`] axes
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Category {
     Cool,
@@ -182,24 +296,58 @@ struct System<'l> {
 }
 
 impl System<'_> {
-    fn validate(&self, part: &Part) -> bool {
-        let mut cur = self.flows[&b"in"[..]].as_ref().expect("Missing start node `in`");
-        loop {
-            let insn = cur.conds
-                .iter()
-                .filter(|c| c.comp.check(part))
-                .map(|c| c.insn)
-                .next()
-                .unwrap_or(cur.term);
-
-            let idx = match insn {
-                Insn::Accept => return true,
-                Insn::Reject => return false,
-                Insn::Goto(idx) => idx,
-            };
+    fn name(&self, idx: usize) -> String {
+        String::from_utf8_lossy(self.flows.get_index(idx).unwrap().0).into_owned()
+    }
+
+    /// Compiles the workflows from `in`, failing if any are missing or
+    /// could loop forever
+    fn compile(&self) -> Result<Compiled, CompileError> {
+        let start = self.flows.get_index_of(&b"in"[..]).context(StartSnafu)?;
+        if let Some(idx) = self.flows.values().position(Option::is_none) {
+            return UndefinedSnafu { label: self.name(idx) }.fail();
+        }
 
-            cur = self.flows[idx].as_ref().expect("Missing referenced node");
+        let live = self.flows.values().flatten().map(|flow| vec![false; flow.conds.len() + 1]).collect();
+        let mut compiler = Compiler { system: self, live, stack: Vec::new() };
+        let tree = compiler.insn(Insn::Goto(start), ALL_PARTS)?;
+
+        let mut dead = Vec::new();
+        let mut unreachable = Vec::new();
+        for (idx, live) in compiler.live.iter().enumerate() {
+            if !live.contains(&true) {
+                unreachable.push(idx);
+            } else {
+                dead.extend(live.iter().positions(|&l| !l).map(|rule| (idx, rule)));
+            }
+        }
+
+        Ok(Compiled { tree, dead, unreachable })
+    }
+
+    /// Describes the workflows in Graphviz DOT, with each rule as an edge
+    /// labelled by its condition and the fallback rule labelled `else`
+    fn to_dot(&self) -> String {
+        let accept = self.flows.len();
+        let reject = accept + 1;
+        let target = |insn| match insn {
+            Insn::Goto(idx) => idx,
+            Insn::Accept => accept,
+            Insn::Reject => reject,
+        };
+
+        let mut dot = Dot::digraph("workflows");
+        dot.node(accept, 'A', &[("shape", "doublecircle")]);
+        dot.node(reject, 'R', &[("shape", "doublecircle")]);
+        for (idx, (label, flow)) in self.flows.iter().enumerate() {
+            dot.node(idx, String::from_utf8_lossy(label), &[("shape", "box")]);
+            let Some(flow) = flow else { continue };
+            for rule in &flow.conds {
+                dot.labelled_edge(idx, target(rule.insn), &rule.comp);
+            }
+            dot.labelled_edge(idx, target(flow.term), "else");
         }
+        dot.finish()
     }
 }
 
@@ -1,28 +1,301 @@
-use crate::prelude::*;
+use std::collections::VecDeque;
 
-day!(20);
+use crate::{prelude::*, utils::{dot::Dot, parser::Interner}};
 
+day!(20);
 
+/// A pulse level, `true` for high
 type Signal = bool;
 
+/// Presses to try in part 2 before giving up on finding every cycle
+const MAX_PRESSES: u64 = 1 << 20;
 
 pub fn run() -> Result<(), Whatever> {
     let input = whatever!(load_input_bytes(DAY), "Failed to load input");
+    let mut network = whatever!(parser::parse(&input), "Failed to parse input");
+
+    if crate::has_arg("dot") {
+        println!("{}", network.to_dot());
+    }
 
+    let part1 = whatever!(part1(&mut network.clone()), "Failed doing part 1");
+    println!("Part 1: {part1}");
 
+    let part2 = whatever!(part2(&mut network), "Failed doing part 2");
+    println!("Part 2: {part2}");
 
     Ok(())
 }
 
+fn part1(network: &mut Network<'_>) -> Result<u64, Whatever> {
+    let mut counts = [0u64; 2];
+    for _ in 0..1000 {
+        network.press(|pulse| counts[usize::from(pulse.signal)] += 1)?;
+    }
+    Ok(counts[0] * counts[1])
+}
+
+/// The module feeding `rx` is a conjunction of several independent
+/// sub-circuits, each sending it a high pulse once per period. `rx` gets a
+/// low pulse once they all line up, at the LCM of the periods.
+fn part2(network: &mut Network<'_>) -> Result<u64, Whatever> {
+    let Some(rx) = network.names.id(b"rx") else {
+        whatever!("No rx module");
+    };
+    let feeders = network.inputs(rx);
+    let &[feeder] = &feeders[..] else {
+        whatever!("Expected a single module feeding rx, found {}", feeders.len());
+    };
+    ensure_whatever!(
+        matches!(network.nodes[feeder].module, Module::Conjunction(_)),
+        "Module feeding rx is not a conjunction"
+    );
+
+    // Presses at which each input first and second sent a high pulse
+    let inputs = network.inputs(feeder);
+    let mut hits: Vec<Vec<u64>> = vec![Vec::new(); inputs.len()];
+    for presses in 1..=MAX_PRESSES {
+        network.press(|pulse| {
+            if pulse.to == feeder && pulse.signal {
+                let slot = inputs.iter().position(|&i| i == pulse.from).unwrap();
+                // An input may send several high pulses in one press
+                if hits[slot].len() < 2 && hits[slot].last() != Some(&presses) {
+                    hits[slot].push(presses);
+                }
+            }
+        })?;
+
+        if hits.iter().all(|h| h.len() == 2) {
+            let mut lcm = 1;
+            for h in &hits {
+                let period = h[1] - h[0];
+                ensure_whatever!(period > 0, "Sub-circuit has a cycle of length 0");
+                ensure_whatever!(h[0] == period, "Sub-circuit cycle of {period} is offset by {}", h[0] % period);
+                lcm = num::integer::lcm(lcm, period);
+            }
+            return Ok(lcm);
+        }
+    }
+
+    whatever!("No cycles found within {MAX_PRESSES} presses")
+}
+
+/// A pulse sent from one module to another
+#[derive(Debug, Clone, Copy)]
+struct Pulse {
+    from: usize,
+    to: usize,
+    signal: Signal,
+}
 
 trait Component {
-    // fn pulse(&mut self, inputs: &[], outputs: &mut []);
+    /// Handles a pulse arriving on input `slot`, returning the pulse to send
+    /// to every output, if any
+    fn pulse(&mut self, slot: usize, signal: Signal) -> Option<Signal>;
 }
 
+#[derive(Debug, Clone, Default)]
 struct FlipFlop {
     state: bool,
 }
 
+impl Component for FlipFlop {
+    fn pulse(&mut self, _slot: usize, signal: Signal) -> Option<Signal> {
+        if signal {
+            return None;
+        }
+        self.state = !self.state;
+        Some(self.state)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 struct Conjunction {
-    state: bool,
+    /// Last signal received on each input
+    state: Vec<Signal>,
+    /// Number of inputs last sent a high pulse
+    highs: usize,
+}
+
+impl Component for Conjunction {
+    fn pulse(&mut self, slot: usize, signal: Signal) -> Option<Signal> {
+        let last = std::mem::replace(&mut self.state[slot], signal);
+        match (last, signal) {
+            (false, true) => self.highs += 1,
+            (true, false) => self.highs -= 1,
+            _ => {}
+        }
+        Some(self.highs != self.state.len())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Module {
+    /// Repeats every pulse
+    Broadcaster,
+    FlipFlop(FlipFlop),
+    Conjunction(Conjunction),
+    /// Only receives pulses, like `rx`
+    Output,
+}
+
+impl Component for Module {
+    fn pulse(&mut self, slot: usize, signal: Signal) -> Option<Signal> {
+        match self {
+            Module::Broadcaster => Some(signal),
+            Module::FlipFlop(ff) => ff.pulse(slot, signal),
+            Module::Conjunction(conj) => conj.pulse(slot, signal),
+            Module::Output => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    module: Module,
+    /// Each output's module id and input slot on that module
+    outputs: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+struct Network<'i> {
+    names: Interner<'i>,
+    nodes: Vec<Node>,
+    broadcaster: usize,
+    queue: VecDeque<(Pulse, usize)>,
+}
+
+impl<'i> Network<'i> {
+    /// Connects the modules, with each line of `defs` giving a module's name
+    /// id, its module if not just an output, and the ids of its outputs
+    fn new(names: Interner<'i>, defs: Vec<(usize, Module, Vec<usize>)>) -> Result<Self, Whatever> {
+        let Some(broadcaster) = names.id(b"broadcaster") else {
+            whatever!("No broadcaster module");
+        };
+
+        let mut nodes: Vec<Node> = (0..names.len())
+            .map(|_| Node { module: Module::Output, outputs: Vec::new() })
+            .collect();
+        let mut slots = vec![0; names.len()];
+        for (id, module, outputs) in defs {
+            nodes[id].module = module;
+            nodes[id].outputs = outputs
+                .into_iter()
+                .map(|out| {
+                    slots[out] += 1;
+                    (out, slots[out] - 1)
+                })
+                .collect();
+        }
+
+        // Conjunctions remember a signal per input
+        for (node, &count) in nodes.iter_mut().zip(&slots) {
+            if let Module::Conjunction(conj) = &mut node.module {
+                conj.state = vec![false; count];
+            }
+        }
+
+        Ok(Network { names, nodes, broadcaster, queue: VecDeque::new() })
+    }
+
+    /// Ids of the modules sending to `id`, in input slot order
+    fn inputs(&self, id: usize) -> Vec<usize> {
+        let mut inputs: Vec<(usize, usize)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(from, node)| {
+                node.outputs
+                    .iter()
+                    .filter(|&&(to, _)| to == id)
+                    .map(move |&(_, slot)| (slot, from))
+            })
+            .collect();
+        inputs.sort_unstable();
+        inputs.into_iter().map(|(_, from)| from).collect()
+    }
+
+    /// Describes the network in Graphviz DOT, with modules labelled by name
+    /// and type prefix, like `%ab` for a flip-flop
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::digraph("modules");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let (prefix, shape) = match node.module {
+                Module::Broadcaster => ("", "doublecircle"),
+                Module::FlipFlop(_) => ("%", "box"),
+                Module::Conjunction(_) => ("&", "diamond"),
+                Module::Output => ("", "circle"),
+            };
+            let name = String::from_utf8_lossy(self.names.label(id));
+            dot.node(id, format_args!("{prefix}{name}"), &[("shape", shape)]);
+            for &(to, _) in &node.outputs {
+                dot.edge(id, to);
+            }
+        }
+        dot.finish()
+    }
+
+    /// Pushes the button, passing every pulse sent to `on_pulse` in the
+    /// order they are handled
+    fn press<F: FnMut(Pulse)>(&mut self, mut on_pulse: F) -> Result<(), Whatever> {
+        ensure_whatever!(self.queue.is_empty(), "Pulse queue not empty");
+
+        // The button sends a low pulse to the broadcaster, shown as coming
+        // from the broadcaster itself
+        let button = Pulse { from: self.broadcaster, to: self.broadcaster, signal: false };
+        self.queue.push_back((button, 0));
+
+        while let Some((pulse, slot)) = self.queue.pop_front() {
+            on_pulse(pulse);
+            let node = &mut self.nodes[pulse.to];
+            if let Some(signal) = node.module.pulse(slot, pulse.signal) {
+                for &(to, slot) in &node.outputs {
+                    self.queue.push_back((Pulse { from: pulse.to, to, signal }, slot));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod parser {
+    use nom::{
+        branch::alt,
+        bytes::complete::tag,
+        character::complete::{alpha1, char},
+        combinator::{all_consuming, opt, value},
+        error::Error,
+        multi::separated_list1,
+        sequence::{pair, preceded},
+        Finish,
+    };
+    use snafu::{ResultExt, Whatever};
+
+    use super::{Conjunction, FlipFlop, Module, Network};
+    use crate::utils::{parser::{for_each, interned, line, Interner}, NomFail};
+
+    pub fn parse(input: &[u8]) -> Result<Network<'_>, Whatever> {
+        let mut names = Interner::new();
+        let mut defs = Vec::new();
+
+        let mut name = interned(&mut names, alpha1::<_, Error<_>>);
+        let res = all_consuming(for_each(
+            |input| {
+                let kind = alt((
+                    value(Module::FlipFlop(FlipFlop::default()), char('%')),
+                    value(Module::Conjunction(Conjunction::default()), char('&')),
+                ));
+                let (input, (kind, id)) = pair(opt(kind), &mut name)(input)?;
+                let (input, outputs) = line(preceded(tag(" -> "), separated_list1(tag(", "), &mut name)))(input)?;
+                Ok((input, (id, kind.unwrap_or(Module::Broadcaster), outputs)))
+            },
+            |def| defs.push(def),
+        ))(input)
+        .finish();
+        drop(name);
+        res.map_err(NomFail::from).whatever_context("Invalid module definition")?;
+
+        Network::new(names, defs)
+    }
 }
@@ -1,6 +1,6 @@
-use std::{collections::{HashMap, HashSet}, io::Write};
+use std::{collections::HashSet, io::Write};
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::{dot::Dot, parser::Interner}};
 use rand::Rng;
 use self::parser::parse_graph;
 
@@ -8,8 +8,12 @@ day!(25);
 
 pub fn run() -> Result<(), Whatever> {
     let input = whatever!(load_input_bytes(DAY), "Failed to load input");
-    let graph = whatever!(parse_graph(&input), "Failed to parse input");
-    
+    let (symbols, graph) = whatever!(parse_graph(&input), "Failed to parse input");
+
+    if crate::has_arg("dot") {
+        println!("{}", graph.to_dot(&symbols));
+    }
+
     let graph = loop {
         let mut graph = graph.clone();
         graph.fully_contract();
@@ -22,8 +26,8 @@ pub fn run() -> Result<(), Whatever> {
     for node in &graph.nodes {
         if !node.is_empty() {
             write!(&mut stdout, "{}: ", node.len()).unwrap();
-            for l in node {
-                stdout.write_all(l).unwrap();
+            for &l in node {
+                stdout.write_all(symbols.label(l)).unwrap();
                 stdout.write_all(b" ").unwrap();
             }
             stdout.write_all(b"\n\n").unwrap();
@@ -35,16 +39,34 @@ pub fn run() -> Result<(), Whatever> {
     Ok(())
 }
 
-type Label = [u8; 3];
-
 #[derive(Default, Debug, Clone)]
 struct Graph {
-    labels: HashMap<Label, usize>,
-    nodes: Vec<HashSet<Label>>,
+    nodes: Vec<HashSet<usize>>,
     edges: Vec<[usize; 2]>,
 }
 
 impl Graph {
+    pub fn new(len: usize, edges: Vec<[usize; 2]>) -> Self {
+        let nodes = (0..len).map(|i| HashSet::from([i])).collect();
+        Self { nodes, edges }
+    }
+
+    /// Describes the graph in Graphviz DOT, labelling each remaining node
+    /// with the names of the components merged into it
+    pub fn to_dot(&self, symbols: &Interner<'_>) -> String {
+        let mut dot = Dot::graph("wires");
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if !node.is_empty() {
+                let names = node.iter().map(|&l| String::from_utf8_lossy(symbols.label(l))).sorted().join(" ");
+                dot.node(idx, names, &[]);
+            }
+        }
+        for &[left, right] in &self.edges {
+            dot.edge(left, right);
+        }
+        dot.finish()
+    }
+
     pub fn fully_contract(&mut self) {
         let Some(iters) = self.nodes.len().checked_sub(2) else {
             return;
@@ -58,15 +80,6 @@ impl Graph {
         }
     }
 
-    pub fn insert_label(&mut self, label: Label) -> usize {
-        *self.labels.entry(label)
-            .or_insert_with(|| {
-                let idx = self.nodes.len();
-                self.nodes.push(HashSet::from([label]));
-                idx
-            })
-    }
-
     pub fn contract(&mut self, n: usize) {
         // Remove self edge
         let [left, right] = self.edges.swap_remove(n);
@@ -110,55 +123,37 @@ fn get_two_mut<T>(slice: &mut [T], one: usize, two: usize) -> [&mut T; 2] {
 
 mod parser {
     use nom::{
-        branch::alt,
-        bytes::complete::tag,
-        character::complete::{line_ending, space1},
-        combinator::{eof, verify},
-        multi::fold_many1,
+        character::complete::{alpha1, char, space1},
+        combinator::all_consuming,
+        error::Error,
         sequence::{preceded, terminated},
-        Finish, IResult,
+        Finish,
     };
 
-    use crate::utils::{parser::many_array, NomFail};
-
-    use super::{Graph, Label};
-
-    pub fn parse_graph(input: &[u8]) -> Result<Graph, NomFail> {
-        match parse_graph_(input).finish() {
-            Ok((_, g)) => Ok(g),
-            Err(err) => Err(err.into()),
-        }
-    }
-
-    fn parse_graph_(mut input: &[u8]) -> IResult<&[u8], Graph> {
-        let mut graph = Graph::default();
-        while !input.is_empty() {
-            // Parse prefix
-            let pre;
-            (input, pre) = terminated(label, tag(":"))(input)?;
-
-            // Insert prefix
-            let pre = graph.insert_label(pre);
-
-            // Parse adjacencies
-            (input, ()) = fold_many1(
-                preceded(space1, label),
-                || (),
-                |(), n| {
-                    // Add label and adjacency
-                    let right = graph.insert_label(n);
-                    graph.edges.push([pre, right]);
-                },
-            )(input)?;
-            (input, _) = alt((eof, line_ending))(input)?;
-        }
-        _ = eof(input)?;
-        Ok((input, graph))
-    }
-
-    fn label(input: &[u8]) -> IResult<&[u8], Label> {
-        many_array(verify(nom::number::complete::u8, |b| {
-            (b'a'..=b'z').contains(b)
-        }))(input)
+    use crate::utils::{parser::{for_each, interned, line, Interner}, NomFail};
+
+    use super::Graph;
+
+    pub fn parse_graph(input: &[u8]) -> Result<(Interner<'_>, Graph), NomFail> {
+        let mut symbols = Interner::new();
+        let mut edges = Vec::new();
+
+        let mut label = interned(&mut symbols, alpha1::<_, Error<_>>);
+        all_consuming(for_each(
+            |input| {
+                // Each line lists a node followed by at least one neighbor
+                let (input, pre) = terminated(&mut label, char(':'))(input)?;
+                let (input, first) = preceded(space1, &mut label)(input)?;
+                edges.push([pre, first]);
+                let (input, ()) = line(for_each(preceded(space1, &mut label), |n| edges.push([pre, n])))(input)?;
+                Ok((input, ()))
+            },
+            |()| (),
+        ))(input)
+        .finish()?;
+        drop(label);
+
+        let graph = Graph::new(symbols.len(), edges);
+        Ok((symbols, graph))
     }
 }
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
-use crate::{prelude::*, utils::NomFail};
+use crate::{prelude::*, utils::{dot::Dot, numtheory, parser::Interner, NomFail}};
 
 day!(8);
 
-fn part1(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
-    let start = graph.labels[b"AAA"];
+fn part1(graph: &Graph<'_>, dirs: &[Dir]) -> Result<u64, Whatever> {
+    let Some(start) = graph.labels.id(b"AAA") else {
+        whatever!("No start node");
+    };
 
     let mut count = 0u64;
     let mut idx = start;
@@ -17,7 +17,7 @@ fn part1(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
             whatever!("Failed to follow node");
         };
 
-        if fnode == *b"ZZZ" {
+        if fnode == b"ZZZ" {
             return Ok(count);
         }
 
@@ -25,18 +25,20 @@ fn part1(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
     }
 }
 
-fn part2(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
+fn part2(graph: &Graph<'_>, dirs: &[Dir]) -> Result<u64, Whatever> {
     let mut nodes: Vec<usize> = graph
-        .nodes
+        .labels
+        .labels()
         .iter()
         .enumerate()
-        .filter(|(_, n)| n.label[2] == b'A')
+        .filter(|(_, l)| l.ends_with(b"A"))
         .map(|(i, _)| i)
         .collect();
 
+    // Steps until each start node first reaches an end node
     let mut count = 0u64;
     let mut dir_iter = dirs.iter().copied().cycle();
-    let mut lcm = 1u64;
+    let mut periods = Vec::with_capacity(nodes.len());
     while !nodes.is_empty() {
         count += 1;
         let dir = dir_iter.next().expect("Ran out of steps");
@@ -46,8 +48,8 @@ fn part2(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
 
             *idx = fidx;
 
-            if fnode[2] == b'Z' {
-                lcm = num::integer::lcm(lcm, count);
+            if fnode.ends_with(b"Z") {
+                periods.push(count);
                 false
             } else {
                 true
@@ -55,7 +57,7 @@ fn part2(graph: &Graph, dirs: &[Dir]) -> Result<u64, Whatever> {
         });
     }
 
-    Ok(lcm)
+    Ok(numtheory::lcm_all(periods))
 }
 
 pub fn run() -> Result<(), Whatever> {
@@ -66,6 +68,10 @@ pub fn run() -> Result<(), Whatever> {
         .map_err(NomFail::from),
         "Failed to parse input");
 
+    if crate::has_arg("dot") {
+        println!("{}", graph.to_dot());
+    }
+
     let part1 = whatever!(part1(&graph, &dirs), "Failed doing part 1");
     println!("Part 1: {part1}");
 
@@ -81,73 +87,50 @@ enum Dir {
     Right,
 }
 
-type Label = [u8; 3];
-
-#[derive(Debug)]
-struct Node<T> {
-    label: Label,
-    dirs: Option<(T, T)>,
-}
-
 #[derive(Debug, Default)]
-struct Graph {
-    labels: HashMap<Label, usize>,
-    nodes: Vec<Node<usize>>,
+struct Graph<'i> {
+    labels: Interner<'i>,
+    nodes: Vec<Option<(usize, usize)>>,
 }
 
-impl Graph {
-    pub fn follow(&self, idx: usize, dir: Dir) -> Option<(Label, usize)> {
-        let (left, right) = self.nodes.get(idx)?.dirs?;
+impl<'i> Graph<'i> {
+    pub fn follow(&self, idx: usize, dir: Dir) -> Option<(&'i [u8], usize)> {
+        let (left, right) = (*self.nodes.get(idx)?)?;
         let fidx = match dir {
             Dir::Left => left,
             Dir::Right => right,
         };
-        Some((self.nodes[fidx].label, fidx))
+        Some((self.labels.label(fidx), fidx))
     }
 
-    pub fn add_label(&mut self, label: Label) -> usize {
-        *self.labels.entry(label)
-            .or_insert_with(|| {
-                let idx = self.nodes.len();
-                let node = Node { label, dirs: None };
-                self.nodes.push(node);
-                idx
-            })
-    }
-}
-
-impl FromIterator<Node<Label>> for Graph {
-    fn from_iter<T: IntoIterator<Item = Node<Label>>>(iter: T) -> Self {
-        let mut graph = Graph::default();
-        for item in iter {
-            let Node { label, dirs: ldirs } = item;
-            let idx = graph.add_label(label);
-            let dirs = ldirs.map(|(left, right)| 
-                (graph.add_label(left), graph.add_label(right))
-            );
-            graph.nodes[idx].dirs = dirs;
+    /// Describes the graph in Graphviz DOT, with edges labelled `L` or `R`
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::digraph("network");
+        for (idx, &label) in self.labels.labels().iter().enumerate() {
+            dot.node(idx, String::from_utf8_lossy(label), &[]);
+        }
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if let &Some((left, right)) = node {
+                dot.labelled_edge(idx, left, 'L').labelled_edge(idx, right, 'R');
+            }
         }
-        graph
+        dot.finish()
     }
 }
 
 mod parser {
-    use super::{Graph, Label, Node, Dir};
-    use crate::utils::{parser::{many_array, self}, eof_iterator};
+    use super::{Graph, Dir};
+    use crate::utils::parser::{adjacency, for_each, interned, Interner};
     use nom::{
-        character::complete::{char, space1, multispace1},
-        sequence::{tuple, separated_pair},
-        IResult, combinator::{map, value}, multi::many1, branch::alt,
+        character::complete::{alphanumeric1, char, multispace1},
+        sequence::separated_pair,
+        IResult, combinator::{all_consuming, value}, multi::many1, branch::alt,
     };
 
-    pub fn parse_all(input: &[u8]) -> IResult<&[u8], (Vec<Dir>, Graph)> {
+    pub fn parse_all(input: &[u8]) -> IResult<&[u8], (Vec<Dir>, Graph<'_>)> {
         separated_pair(parse_dirs, multispace1, parse_graph)(input)
     }
 
-    fn parse_label(input: &[u8]) -> IResult<&[u8], Label> {
-        many_array(nom::number::complete::u8)(input)
-    }
-
     fn parse_dirs(input: &[u8]) -> IResult<&[u8], Vec<Dir>> {
         let pdir = alt((
             value(Dir::Left, char('L')),
@@ -157,29 +140,19 @@ mod parser {
         many1(pdir)(input)
     }
 
-    fn parse_graph(mut input: &[u8]) -> IResult<&[u8], Graph> {
-        let pline = parser::line(tuple((
-            parse_label,
-            space1,
-            char('='),
-            space1,
-            char('('),
-            parse_label,
-            char(','),
-            space1,
-            parse_label,
-            char(')'),
-        )));
-
-        let pline = map(pline, |(n, _, _, _, _, l, _, _, r, _)| Node {
-            label: n,
-            dirs: Some((l, r)),
-        });
-
-        let mut iter = eof_iterator(input, pline);
-        let graph: Graph = iter.collect();
-        (input, ()) = iter.finish()?;
+    fn parse_graph(input: &[u8]) -> IResult<&[u8], Graph<'_>> {
+        let mut labels = Interner::new();
+        let mut adjs = Vec::new();
+        let (input, ()) = all_consuming(for_each(
+            adjacency(interned(&mut labels, alphanumeric1)),
+            |adj| adjs.push(adj),
+        ))(input)?;
+
+        let mut nodes = vec![None; labels.len()];
+        for (node, dirs) in adjs {
+            nodes[node] = Some(dirs);
+        }
 
-        Ok((input, graph))
+        Ok((input, Graph { labels, nodes }))
     }
 }
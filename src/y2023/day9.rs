@@ -1,6 +1,6 @@
 use std::ops::Add;
 
-use crate::{prelude::*, utils::NomFail};
+use crate::{prelude::*, utils::load_records};
 
 day!(9);
 
@@ -32,20 +32,16 @@ fn predict(nums: &[i64]) -> Option<(i64, i64)> {
 }
 
 pub fn run() -> Result<(), Whatever> {
-    let input = whatever!(load_input_bytes(DAY), "Failed to load input");
-    let mut iter = parser::input_iter(&input);
-
-    let Some((part2, part1)) = iter
-        .map(|x| dbg!(predict(&x)))
-        .fold_options((0i64, 0i64), tuple_add)
-    else {
-        whatever!("Failed to predict");
-    };
-
-    whatever!(
-        iter.finish().finish().map_err(NomFail::from),
-        "Failed to parse input"
-    );
+    let records = whatever!(load_records(DAY, parser::history), "Failed to load input");
+
+    let (mut part1, mut part2) = (0i64, 0i64);
+    for nums in records {
+        let nums = whatever!(nums, "Failed to parse input");
+        let Some((prev, next)) = predict(&nums) else {
+            whatever!("Failed to predict");
+        };
+        (part2, part1) = tuple_add((part2, part1), (prev, next));
+    }
 
     println!("Part 1: {part1}\nPart 2: {part2}");
 
@@ -57,20 +53,11 @@ fn tuple_add<O, U, T: Add<U, Output = O>>(a: (T, T), b: (U, U)) -> (O, O) {
 }
 
 mod parser {
-    use nom::{character::complete::space1, error::Error, multi::separated_list1, IResult};
+    use nom::{character::complete::space1, multi::separated_list1, IResult};
 
-    use crate::utils::{eof_iterator, parser::line, EofParserIterator};
+    use crate::utils::parser::line;
 
-    pub fn input_iter<'i>(
-        input: &'i [u8],
-    ) -> EofParserIterator<
-        &'i [u8],
-        Error<&'i [u8]>,
-        impl FnMut(&'i [u8]) -> IResult<&'i [u8], Vec<i64>>,
-    > {
-        eof_iterator(
-            input,
-            line(separated_list1(space1, nom::character::complete::i64)),
-        )
+    pub fn history(input: &[u8]) -> IResult<&[u8], Vec<i64>> {
+        line(separated_list1(space1, nom::character::complete::i64))(input)
     }
 }
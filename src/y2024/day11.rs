@@ -1,22 +1,23 @@
 use nom::Finish;
 
 use super::Solution;
-use crate::utils::NomFail;
+use crate::utils::{memo::Memo, NomFail};
 
 day!(run 11);
 
-type Memo = std::cell::RefCell<std::collections::HashMap<(u64, usize), u64>>;
+/// Stones a stone turns into, keyed by its number and remaining blinks
+type StoneMemo = Memo<(u64, usize), u64>;
 
 struct Day11 {
     nums: Vec<u64>,
-    memo: Memo,
+    memo: StoneMemo,
 }
 
 impl<'i> Solution<'i> for Day11 {
     fn parse(input: &'i mut Vec<u8>) -> Result<Self, NomFail> {
         Ok(Self {
             nums: parse::parse(input).finish()?.1,
-            memo: Memo::default(),
+            memo: StoneMemo::new(),
         })
     }
 
@@ -30,37 +31,30 @@ impl<'i> Solution<'i> for Day11 {
 }
 
 impl Day11 {
-    pub fn count_stones(&self, blinks: usize) -> u64 {
-        self.nums
-            .iter()
-            .map(|&n| count_stones(n, blinks, &self.memo))
+    pub fn count_stones(&mut self, blinks: usize) -> u64 {
+        let Self { nums, memo } = self;
+        nums.iter()
+            .map(|&n| count_stones(n, blinks, memo))
             .sum()
     }
 }
 
-fn count_stones(n: u64, blinks: usize, memo: &Memo) -> u64 {
+fn count_stones(n: u64, blinks: usize, memo: &mut StoneMemo) -> u64 {
     // Stop at zero blinks
     let Some(blinks) = blinks.checked_sub(1) else {
         return 1;
     };
 
-    // Retrieve from cache if available
-    if let Some(&c) = memo.borrow().get(&(n, blinks)) {
-        return c;
-    }
-
-    // Calculate new number
-    let c = if n == 0 {
-        count_stones(1, blinks, memo)
-    } else if let Some([a, b]) = split(n) {
-        count_stones(a, blinks, memo) + count_stones(b, blinks, memo)
-    } else {
-        count_stones(n * 2024, blinks, memo)
-    };
-
-    // Add value to cache
-    memo.borrow_mut().insert((n, blinks), c);
-    c
+    // Calculate new number, unless already cached
+    memo.get_or_insert_with((n, blinks), |memo| {
+        if n == 0 {
+            count_stones(1, blinks, memo)
+        } else if let Some([a, b]) = split(n) {
+            count_stones(a, blinks, memo) + count_stones(b, blinks, memo)
+        } else {
+            count_stones(n * 2024, blinks, memo)
+        }
+    })
 }
 
 /// Splits n in half if there's an even number of digits
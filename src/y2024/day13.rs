@@ -1,5 +1,6 @@
 use super::Solution;
-use crate::utils::NomFail;
+use crate::utils::{numtheory, NomFail};
+use num::rational::Ratio;
 
 day!(run 13);
 
@@ -42,33 +43,25 @@ impl<'i> Solution<'i> for Day13 {
 impl Game {
     /// Returns the cost to win, if possible
     pub fn cost(&self) -> Option<u64> {
-        let bc = self.b[0] * self.a[1];
-        let ad = self.a[0] * self.b[1];
-        let bf = self.b[0] * self.prize[1];
-        let de = self.b[1] * self.prize[0];
-
-        if (ad > bc) != (de > bf) {
-            return None;
-        }
-
-        let x = divide(bf.abs_diff(de), bc.abs_diff(ad))?;
-        let y = divide(self.prize[0].checked_sub(self.a[0] * x)?, self.b[0])?;
-
-        Some(x * ACOST + y * BCOST)
+        let wide = |n: u64| i128::from(n);
+        let matrix = [
+            [wide(self.a[0]), wide(self.b[0])],
+            [wide(self.a[1]), wide(self.b[1])],
+        ];
+        let [x, y] = numtheory::solve_linear(matrix, self.prize.map(wide))?;
+
+        // Buttons can only be pressed a whole, non-negative number of times
+        let presses = |n: Ratio<i128>| n.is_integer().then(|| n.to_integer()).and_then(|n| u64::try_from(n).ok());
+        Some(presses(x)? * ACOST + presses(y)? * BCOST)
     }
 }
 
-fn divide(a: u64, b: u64) -> Option<u64> {
-    a.checked_div(b).filter(|_| a % b == 0)
-}
-
 mod parse {
     use nom::{
-        bytes::complete::tag,
-        character::complete::{line_ending, u64},
+        character::complete::line_ending,
         combinator::all_consuming,
         multi::separated_list1,
-        sequence::{preceded, separated_pair, tuple},
+        sequence::tuple,
         Finish, IResult, Parser,
     };
 
@@ -76,30 +69,18 @@ mod parse {
 
     use super::Game;
 
-    fn button(label: u8) -> impl FnMut(&[u8]) -> IResult<&[u8], (u64, u64)> {
-        move |input: &[u8]| {
-            line(preceded(
-                tuple((tag("Button "), tag([label]), tag(": X+"))),
-                separated_pair(u64, tag(", Y+"), u64),
-            ))(input)
-        }
-    }
-
-    fn prize(input: &[u8]) -> IResult<&[u8], (u64, u64)> {
-        line(preceded(
-            tag("Prize: X="),
-            separated_pair(u64, tag(", Y="), u64),
-        ))(input)
-    }
-
     fn game(input: &[u8]) -> IResult<&[u8], Game> {
-        tuple((button(b'A'), button(b'B'), prize))
-            .map(|(a, b, p)| Game {
-                a: a.into(),
-                b: b.into(),
-                prize: p.into(),
-            })
-            .parse(input)
+        tuple((
+            line(format_parser!("Button A: X+{}, Y+{}"; u64, u64)),
+            line(format_parser!("Button B: X+{}, Y+{}"; u64, u64)),
+            line(format_parser!("Prize: X={}, Y={}"; u64, u64)),
+        ))
+        .map(|(a, b, p)| Game {
+            a: a.into(),
+            b: b.into(),
+            prize: p.into(),
+        })
+        .parse(input)
     }
 
     pub fn parse(input: &[u8]) -> Result<Vec<Game>, NomFail> {
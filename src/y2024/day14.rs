@@ -1,4 +1,4 @@
-use crate::utils::{Coord, sgrid::Grid, NomFail};
+use crate::utils::{Coord, numtheory::{self, Congruence, CrtError}, record::Recorder, sgrid::Grid, NomFail};
 use super::Solution;
 use std::{io::Write, sync::atomic::{AtomicBool, Ordering}};
 
@@ -41,7 +41,34 @@ impl<'i> Solution<'i> for Day14 {
         quads.into_iter().product()
     }
 
-    fn part2(&mut self) -> u64 {
+    fn try_part2(&mut self) -> Result<u64, CrtError> {
+        if crate::has_arg("interactive") {
+            return Ok(self.view_interactive());
+        }
+
+        // The tree appears when the robots bunch up along both axes at once
+        let tx = self.min_spread(self.width, |c| c.x);
+        let ty = self.min_spread(self.height, |c| c.y);
+
+        // Find the step matching both offsets
+        let (w, h) = (i64::from(self.width), i64::from(self.height));
+        let step = numtheory::crt([
+            Congruence::new(tx as i64, w),
+            Congruence::new(ty as i64, h),
+        ])?;
+        let step = step.residue as u64;
+
+        if let Some(mut rec) = Recorder::from_args(4) {
+            self.record(&mut rec, step);
+        }
+        Ok(step)
+    }
+}
+
+impl Day14 {
+    /// Steps through every frame with no overlapping robots,
+    /// asking on stdin whether it shows a tree
+    fn view_interactive(&mut self) -> u64 {
         use rayon::prelude::*;
 
         let width = usize::from(self.width);
@@ -84,9 +111,41 @@ impl<'i> Solution<'i> for Day14 {
 
         unreachable!()
     }
-}
 
-impl Day14 {
+    /// Records the robots for the steps leading up to the tree at `tree`
+    fn record(&self, rec: &mut Recorder, tree: u64) {
+        const LEAD_IN: u64 = 50;
+        let (width, height) = (usize::from(self.width), usize::from(self.height));
+        for secs in tree.saturating_sub(LEAD_IN)..=tree {
+            let mut grid = Grid::new(vec![false; width * height], width);
+            for robot in &self.robots {
+                let c = self.roam(robot, secs as u32).map(usize::from);
+                grid[c] = true;
+            }
+            rec.snapshot(&grid, |b| if b { [0, 200, 0] } else { [0, 0, 0] });
+        }
+
+        if let Err(e) = rec.finish("y2024_day14") {
+            eprintln!("Failed to save recording: {e}");
+        }
+    }
+
+    /// Finds the step within one `period` where the robots' positions
+    /// along the `axis` have the lowest variance
+    fn min_spread(&self, period: u8, axis: impl Fn(Coord<u8>) -> u8) -> u64 {
+        let n = self.robots.len() as u64;
+        (0..u32::from(period))
+            .min_by_key(|&secs| {
+                let (sum, sum_sq) = self.robots.iter().fold((0, 0), |(s, sq), robot| {
+                    let v = u64::from(axis(self.roam(robot, secs)));
+                    (s + v, sq + v * v)
+                });
+                // Variance scaled by n²
+                n * sum_sq - sum * sum
+            })
+            .map_or(0, u64::from)
+    }
+
     /// Returns the coordinates of `robot` after roaming for `secs`
     pub fn roam(&self, robot: &Robot, secs: u32) -> Coord<u8> {
         let secs: i64 = secs.into();
@@ -123,34 +182,16 @@ impl Robot {
 mod parse {
     use super::Robot;
     use crate::utils::{
-        parser::{line, NomInt},
+        parser::parse_lines,
         Coord, NomFail,
     };
-    use nom::{
-        bytes::complete::tag,
-        combinator::all_consuming,
-        multi::many1,
-        sequence::{preceded, separated_pair},
-        Finish, IResult, Parser,
-    };
-
-    fn coord<T: NomInt>(input: &[u8]) -> IResult<&[u8], Coord<T>> {
-        separated_pair(T::parse_int, tag([b',']), T::parse_int)
-            .map(|(x, y)| Coord { x, y })
-            .parse(input)
-    }
-
-    fn robot(input: &[u8]) -> IResult<&[u8], Robot> {
-        line(separated_pair(
-            preceded(tag("p="), coord),
-            tag(" v="),
-            coord,
-        ))
-        .map(|(pos, vel)| Robot { pos, vel })
-        .parse(input)
-    }
+    use nom::Parser;
 
     pub fn parse(input: &[u8]) -> Result<Vec<Robot>, NomFail> {
-        Ok(all_consuming(many1(robot))(input).finish()?.1)
+        let robot = format_parser!("p={},{} v={},{}"; u8, u8, i8, i8).map(|(px, py, vx, vy)| Robot {
+            pos: Coord { x: px, y: py },
+            vel: Coord { x: vx, y: vy },
+        });
+        parse_lines(input, robot)
     }
 }
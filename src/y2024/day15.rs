@@ -1,5 +1,10 @@
 use super::Solution;
-use crate::utils::sgrid::{Dir, Grid};
+use crate::utils::{
+    record::Recorder,
+    render::Rgb,
+    sgrid::{Dir, Grid},
+    view::GridView,
+};
 
 day!(run 15);
 
@@ -25,6 +30,18 @@ enum Tile2 {
     RBox,
 }
 
+impl Tile2 {
+    /// Walls in grey and boxes in brown, with a darker right half
+    fn color(self) -> Rgb {
+        match self {
+            Tile2::Empty => [0, 0, 0],
+            Tile2::Wall => [128, 128, 128],
+            Tile2::LBox => [200, 140, 60],
+            Tile2::RBox => [150, 100, 40],
+        }
+    }
+}
+
 type Stack = indexmap::IndexMap<usize, usize>;
 
 impl<'i> Solution<'i> for Day15 {
@@ -45,8 +62,16 @@ impl<'i> Solution<'i> for Day15 {
     fn part2(&mut self) -> usize {
         self.robot *= 2;
         let mut stack = Stack::new();
+        let mut rec = Recorder::from_args(4).map(|r| r.every(20));
         for i in 0..self.moves.len() {
             self.move_robot_p2(self.moves[i], &mut stack);
+            if let Some(rec) = &mut rec {
+                rec.snapshot(&self.grid2, Tile2::color);
+            }
+        }
+
+        if let Some(Err(e)) = rec.map(|rec| rec.finish("y2024_day15")) {
+            eprintln!("Failed to save recording: {e}");
         }
         score(&self.grid2, &Tile2::LBox)
     }
@@ -133,17 +158,13 @@ impl Day15 {
 
 // Doubles the width of every tile
 fn grow(grid: &Grid<Tile1>) -> Grid<Tile2> {
-    let mut elems = Vec::with_capacity(grid.len() * 2);
-    for &tile in grid.iter() {
-        let upd = match tile {
-            Tile1::Empty => [Tile2::Empty; 2],
-            Tile1::Wall => [Tile2::Wall; 2],
-            Tile1::Box => [Tile2::LBox, Tile2::RBox],
-        };
-        elems.extend_from_slice(&upd);
-    }
-
-    Grid::new(elems, 2 * grid.width())
+    grid.scaled(2, 1, |&tile, dx, _| match tile {
+        Tile1::Empty => Tile2::Empty,
+        Tile1::Wall => Tile2::Wall,
+        Tile1::Box if dx == 0 => Tile2::LBox,
+        Tile1::Box => Tile2::RBox,
+    })
+    .to_grid()
 }
 
 mod parse {
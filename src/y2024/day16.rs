@@ -1,10 +1,19 @@
-use crate::utils::sgrid::Dir;
+use itertools::Itertools;
+
+use crate::utils::{
+    search::{self, AllPaths},
+    sgrid::Dir,
+};
 
 use super::Solution;
 
 day!(run 16);
 
-type Grid = crate::utils::sgrid::Grid<Tile>;
+/// Grid of walls
+type Grid = crate::utils::sgrid::Grid<bool>;
+
+/// Position and facing of the reindeer
+type State = (usize, Dir);
 
 struct Day16 {
     grid: Grid,
@@ -12,18 +21,6 @@ struct Day16 {
     end: usize,
 }
 
-struct Tile {
-    wall: bool,
-    score: Option<u64>,
-}
-
-#[derive(Copy, Clone)]
-struct State {
-    idx: usize,
-    score: u64,
-    dir: Dir,
-}
-
 const TURN_PENALTY: u64 = 1_000;
 
 impl<'i> Solution<'i> for Day16 {
@@ -32,134 +29,48 @@ impl<'i> Solution<'i> for Day16 {
     }
 
     fn part1(&mut self) -> u64 {
-        let mut stack = vec![State {
-            idx: self.start,
-            score: 0,
-            dir: Dir::Right,
-        }];
-
-        while let Some(state) = stack.pop() {
-            // Update score for tile, skipping walls or better scores
-            let tile = &mut self.grid[state.idx];
-            if tile.wall || !tile.update_score(state.score) || state.idx == self.end {
-                continue;
-            }
-
-            // Continue next steps
-            let dirs = [
-                (state.dir, 0),
-                (state.dir.right(), TURN_PENALTY),
-                (state.dir.left(), TURN_PENALTY),
-            ];
-            for (dir, penalty) in dirs {
-                if let Some(next_idx) = self.grid.dir_index(state.idx, dir) {
-                    let new_score = state.score + penalty + 1;
-                    stack.push(State {
-                        idx: next_idx,
-                        score: new_score,
-                        dir,
-                    });
-                }
-            }
-        }
-
-        // Get best score from start
-        self.grid[self.end].score.expect("No path to end found")
+        let paths = self.search();
+        self.best_score(&paths).expect("No path to end found")
     }
 
     fn part2(&mut self) -> usize {
-        let mut best = vec![false; self.grid.len()];
-        let mut stack = vec![State {
-            idx: self.start,
-            score: 0,
-            dir: Dir::Right,
-        }];
-        let mut revisit = Vec::<(usize, Dir)>::new();
-
-        while let Some(state) = stack.last().copied() {
-            // Checks if index is an intersection
-            let intersect = || {
-                Dir::ALL
-                .into_iter()
-                .filter_map(|d| self.grid.dir_index(state.idx, d))
-                .filter(|&i| self.grid[i].score.is_some())
-                .count() > 2
-            };
-
-            // Check if still on best path
-            let tscore = self.grid[state.idx].score;
-            let isbest = tscore.is_some_and(|ts| state.score <= ts);
-            let atend = state.idx == self.end;
-            if tscore.is_none() || atend || (!isbest && !intersect()) {
-                // If reached end, add all to best
-                if atend && isbest {
-                    for st in &stack {
-                        best[st.idx] = true;
-                    }
-                }
-
-                // Go to next revisit
-                if let Some(revis) = revisit.pop() {
-                    let rscore = loop {
-                        let pop = stack.last().unwrap();
-                        if pop.idx == revis.0 {
-                            break pop.score;
-                        }
-                        stack.pop();
-                    };
-                    let nidx = self.grid.dir_index(revis.0, revis.1).unwrap();
-                    stack.push(State {
-                        idx: nidx,
-                        score: rscore + TURN_PENALTY + 1,
-                        dir: revis.1,
-                    });
-                } else {
-                    break;
-                }
-
-                continue;
-            }
-
-            // Continue straight
-            if let Some(nidx) = self.grid.dir_index(state.idx, state.dir) {
-                stack.push(State {
-                    idx: nidx,
-                    score: state.score + 1,
-                    dir: state.dir,
-                });
-            }
-
-            // Revisit turns
-            revisit.extend_from_slice(&[
-                (state.idx, state.dir.right()),
-                (state.idx, state.dir.left()),
-            ]);
-        }
-
-        best.into_iter().filter(|&b| b).count()
+        let paths = self.search();
+        let best = self.best_score(&paths);
+        let ends = Dir::ALL
+            .map(|d| (self.end, d))
+            .into_iter()
+            .filter(|s| paths.dist(s) == best);
+        let on_paths = paths.on_paths_to(ends);
+        on_paths.into_iter().map(|(idx, _)| idx).unique().count()
     }
 }
 
-impl Tile {
-    pub fn new(wall: bool) -> Self {
-        Self { wall, score: None }
+impl Day16 {
+    /// Finds the best scores and paths to every state from the start
+    fn search(&self) -> AllPaths<State, u64> {
+        search::dijkstra_all([(self.start, Dir::Right)], |&(idx, dir)| {
+            // Don't continue past the end
+            let fwd = (idx != self.end)
+                .then(|| self.grid.dir_index(idx, dir))
+                .flatten()
+                .filter(|&i| !self.grid[i])
+                .map(|i| ((i, dir), 1));
+            let turns = [dir.right(), dir.left()].map(|d| ((idx, d), TURN_PENALTY));
+            fwd.into_iter().chain(turns)
+        })
     }
 
-    /// Updates the score for this tile to the lower value,
-    /// returning whether `n` is the new best
-    pub fn update_score(&mut self, n: u64) -> bool {
-        match self.score {
-            Some(v) if n >= v => false,
-            _ => {
-                self.score = Some(n);
-                true
-            }
-        }
+    /// Best score of any state at the end
+    fn best_score(&self, paths: &AllPaths<State, u64>) -> Option<u64> {
+        Dir::ALL
+            .into_iter()
+            .filter_map(|d| paths.dist(&(self.end, d)))
+            .min()
     }
 }
 
 mod parse {
-    use super::{Day16, Grid, Tile};
+    use super::{Day16, Grid};
     use crate::utils::{sgrid::GridParseErr, Coord};
     use snafu::{OptionExt, ResultExt, Snafu};
 
@@ -180,7 +91,7 @@ mod parse {
                 }
                 _ => return None,
             };
-            Some(Tile::new(wall))
+            Some(wall)
         })
         .context(GridSnafu)?
         .1;
@@ -1,17 +1,20 @@
 use super::Solution;
-use crate::utils::NomFail;
+use crate::utils::{
+    vm::{self, Reg, Vm, VmError},
+    NomFail,
+};
 use itertools::Itertools;
+use snafu::{OptionExt, Snafu};
 
 day!(run 17);
 
-type Reg = u64;
+/// Most instructions a single run may execute before it's assumed to loop
+const STEP_LIMIT: usize = 1 << 20;
 
 #[derive(Debug)]
 struct Day17 {
     program: Vec<u8>,
     regs: [Reg; 3],
-    ptr: usize,
-    out: Vec<u8>,
 }
 
 impl<'i> Solution<'i> for Day17 {
@@ -19,161 +22,87 @@ impl<'i> Solution<'i> for Day17 {
         parse::parse(input)
     }
 
-    fn part1(&mut self) -> String {
-        self.execute();
-        self.out.iter().join(",")
-    }
-
-    fn part2(&mut self) -> u64 {
-        let cons = part2::find_constraints(&self.program);
-        part2::solve_constraints(&cons).expect("No solution found")
-    }
-}
-
-mod part2 {
-    #[derive(Default)]
-    pub struct Constraint {
-        pub val: u64,
-        pub mask: u64,
-    }
-
-    impl Constraint {
-        pub fn is_compat(&self, other: &Self) -> bool {
-            ((self.val ^ other.val) & self.mask & other.mask) == 0
+    fn try_part1(&mut self) -> Result<String, VmError> {
+        if crate::has_arg("trace") {
+            self.print_trace();
         }
 
-        pub fn combine(&self, other: &Self) -> Option<Self> {
-            self.is_compat(other).then(|| Constraint {
-                val: self.val | other.val,
-                mask: self.mask | other.mask,
-            })
-        }
-    }
-
-    type ConList = Box<[Constraint]>;
-
-    pub fn solve_constraints(cons: &[ConList]) -> Option<u64> {
-        solve_r(cons, Constraint::default())
-    }
-
-    fn solve_r(cons: &[ConList], combo: Constraint) -> Option<u64> {
-        let Some((cur, cons)) = cons.split_first() else {
-            return Some(combo.val);
-        };
-
-        cur.iter()
-            .find_map(|con| solve_r(cons, con.combine(&combo)?))
+        Ok(self.execute()?.iter().join(","))
     }
 
-    pub fn find_constraints(prog: &[u8]) -> Box<[ConList]> {
-        // Initialize with length constraint
-        let mut cons: Vec<ConList> = Vec::with_capacity(prog.len() + 1);
-        let maxbit = 3 * prog.len();
-        cons.push(Box::new([Constraint {
-            val: 0,
-            mask: !((1u64 << maxbit) - 1),
-        }]));
-
-        // Collect constraints from program
-        let con_iter = prog.iter().enumerate().map(|(i, &o)| {
-            (0u64..8)
-                .flat_map(|mut bval| {
-                    let ashift = (3 * i as u64) + (bval ^ 1);
-                    let amask = 7u64 << ashift;
-                    let aval = (u64::from(o) ^ bval ^ 5) << ashift;
-                    let bmask = 7u64 << (3 * i);
-                    bval <<= 3 * i;
-                    let a = Constraint {
-                        val: aval,
-                        mask: amask,
-                    };
-                    let b = Constraint {
-                        val: bval,
-                        mask: bmask,
-                    };
-                    a.combine(&b)
-                })
-                .collect()
-        });
-
-        cons.extend(con_iter);
-        cons.into_boxed_slice()
+    fn try_part2(&mut self) -> Result<Reg, NoQuineError> {
+        self.find_quine().context(NoQuineSnafu)
     }
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(display("No value of register A makes the program output itself"))]
+struct NoQuineError;
+
 impl Day17 {
     pub fn new(rega: Reg, regb: Reg, regc: Reg, program: Vec<u8>) -> Self {
         Self {
             program,
             regs: [rega, regb, regc],
-            ptr: 0,
-            out: Vec::new(),
         }
     }
 
-    pub fn execute(&mut self) {
-        self.ptr = 0;
-        self.out.clear();
-        let regs = self.regs;
-
-        while let Some(&[op, val]) = self.program.get(self.ptr..self.ptr + 2) {
-            self.ptr += 2;
-            self.do_op(op, val);
+    /// Runs the program to completion, returning its output
+    pub fn execute(&self) -> Result<Vec<u8>, VmError> {
+        self.execute_with(self.regs[0])
+    }
+
+    /// Runs the program with register A set to `a`, returning its output
+    pub fn execute_with(&self, a: Reg) -> Result<Vec<u8>, VmError> {
+        let [_, b, c] = self.regs;
+        let mut vm = Vm::new(&self.program, [a, b, c]);
+        vm.set_limit(STEP_LIMIT);
+        vm.run()?;
+        Ok(vm.into_output())
+    }
+
+    /// Finds the lowest positive A for which the program outputs itself.
+    ///
+    /// Programs of this kind print once per loop and shift A right by three
+    /// bits each time, so the last output depends only on the top three bits
+    /// of A. A is built three bits at a time by keeping every candidate whose
+    /// output matches the end of the program, working backwards.
+    pub fn find_quine(&self) -> Option<Reg> {
+        let mut cands: Vec<Reg> = vec![0];
+        for start in (0..self.program.len()).rev() {
+            let want = &self.program[start..];
+            cands = cands
+                .iter()
+                .filter(|&&a| a.leading_zeros() >= 3)
+                .flat_map(|&a| (0..8).map(move |bits| a << 3 | bits))
+                .filter(|&a| self.execute_with(a).is_ok_and(|out| out == want))
+                .collect();
         }
 
-        self.regs = regs;
+        // Candidates are built in increasing order
+        cands
+            .into_iter()
+            .filter(|&a| a != 0)
+            .find(|&a| self.execute_with(a).is_ok_and(|out| out == self.program))
     }
 
-    pub fn do_op(&mut self, op: u8, val: u8) {
-        match op {
-            // adv - division on A
-            0 => self.regs[0] = self.div(val),
-
-            // bxl - xor on B
-            1 => self.regs[1] ^= Reg::from(val),
-
-            // bst - B = combo % 8
-            2 => self.regs[2] = self.combo(val) % 8,
-
-            // jnz - jump to val if A!=0
-            3 => {
-                if self.regs[0] != 0 {
-                    self.ptr = usize::from(val);
-                }
-            }
-
-            // bxc - B ^= C
-            4 => self.regs[1] ^= self.regs[2],
-
-            // out - outputs combo % 8
-            5 => self.out.push((self.combo(val) % 8) as u8),
-
-            // bdv - division on B
-            6 => self.regs[1] = self.div(val),
-
-            // cdv - division on C
-            7 => self.regs[2] = self.div(val),
-
-            8.. => panic!("Invalid opcode {op}"),
+    /// Prints the disassembled program and the registers after each step
+    fn print_trace(&self) {
+        match vm::disassemble(&self.program) {
+            Ok(listing) => println!("{listing}"),
+            Err(e) => println!("Failed to disassemble: {e}"),
         }
-    }
 
-    fn combo(&self, val: u8) -> Reg {
-        match val {
-            0..=3 => Reg::from(val),
-            4 => self.regs[0],
-            5 => self.regs[1],
-            6 => self.regs[2],
-            7.. => panic!("Invalid operand {val}"),
+        let mut vm = Vm::new(&self.program, self.regs);
+        vm.set_limit(STEP_LIMIT);
+        vm.enable_trace();
+        let res = vm.run();
+        for state in vm.trace() {
+            println!("{state}");
+        }
+        if let Err(e) = res {
+            println!("{e}");
         }
-    }
-
-    fn div(&self, val: u8) -> u64 {
-        self.combo(val)
-            .try_into()
-            .ok()
-            .and_then(|c| self.regs[0].checked_shr(c))
-            .unwrap_or(0)
     }
 }
 
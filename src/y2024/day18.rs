@@ -1,5 +1,5 @@
 use super::Solution;
-use crate::utils::{opt_min, sgrid::{Dir, Grid}, Coord, NomFail};
+use crate::utils::{search, sgrid::{Dir, Grid}, Coord, NomFail};
 
 day!(run 18);
 
@@ -22,33 +22,21 @@ impl<'i> Solution<'i> for Day18 {
         let ncorrupt = if crate::get_small() { 12 } else { 1024 };
         self.corrupt(ncorrupt);
 
-        // State
-        let end = self.grid.len() - 1;
-        let mut stack = vec![[0usize; 2]];
-        let mut dists = vec![None; self.grid.len()];
-
         // Traverse
-        while let Some([idx, dist]) = stack.pop() {
-            // Skip walls
-            if self.grid[idx] {
-                continue
-            }
-
-            // Update dist, stop if worse or at end
-            if !opt_min(&mut dists[idx], dist) || idx == end {
-                continue
-            }
-
-            // Traverse
-            let ndist = dist + 1;
-            for dir in Dir::ALL {
-                if let Some(nidx) = self.grid.dir_index(idx, dir) {
-                    stack.push([nidx, ndist]);
-                }
-            }
-        }
-
-        dists.last().copied().flatten().expect("No path found")
+        let end = self.grid.len() - 1;
+        let grid = &self.grid;
+        let paths = search::bfs(
+            [0],
+            |&idx| {
+                Dir::ALL
+                    .into_iter()
+                    .filter_map(move |dir| grid.dir_index(idx, dir))
+                    .filter(|&nidx| !grid[nidx])
+            },
+            |&idx| idx == end,
+        );
+
+        paths.goal_dist().expect("No path found")
     }
 
     fn part2(&mut self) -> Coord<u8> {
@@ -1,16 +1,11 @@
 use super::Solution;
-use crate::utils::{
-    opt_min,
-    sgrid::{Dir, Grid},
-    Coord,
-};
+use crate::utils::{search::DistFields, sgrid::Grid, Coord};
 
 day!(run 20);
 
 struct Day20 {
     grid: Grid<bool>,
-    dists: Box<[Option<usize>]>,
-    start: usize,
+    fields: DistFields,
 }
 
 impl<'i> Solution<'i> for Day20 {
@@ -19,29 +14,11 @@ impl<'i> Solution<'i> for Day20 {
     }
 
     fn part1(&mut self) -> usize {
-        self.calc_dists();
-        (0..self.grid.len())
-            .map(|i| self.time_saving(i))
-            .filter(|&n| n >= 100)
-            .count()
+        self.count_cheats(2)
     }
 
     fn part2(&mut self) -> usize {
-        let width = self.grid.width();
-        self.dists
-            .iter()
-            .enumerate()
-            .filter_map(|(i, d)| d.map(|d| (i, d)))
-            .map(|(idx, dist)| {
-                self.iter_dist(idx, 20)
-                    .filter_map(|(c, diff)| {
-                        let d2 = self.dists[c.to_idx(width)]?;
-                        Some(dist.saturating_sub(d2 + diff))
-                    })
-                    .filter(|&n| n >= 100)
-                    .count()
-            })
-            .sum()
+        self.count_cheats(20)
     }
 }
 
@@ -63,46 +40,19 @@ impl Day20 {
         })
     }
 
-    fn calc_dists(&mut self) {
-        let mut idx = self.start;
-        let mut dist = 0;
-        let mut last_dir = None;
-        loop {
-            self.dists[idx] = Some(dist);
-            dist += 1;
-            let mut next = None;
-            for dir in Dir::ALL {
-                if last_dir.is_none_or(|ld| dir != ld) {
-                    if let Some(nidx) = self.grid.dir_index(idx, dir) {
-                        if !self.grid[nidx] {
-                            next = Some(nidx);
-                            last_dir = Some(dir.opposite());
-                            break;
-                        }
-                    }
-                }
-            }
-            match next {
-                Some(i) => idx = i,
-                None => break,
-            }
-        }
-    }
-
-    fn time_saving(&self, idx: usize) -> usize {
-        if !self.grid[idx] {
-            return 0;
-        }
-
-        let mut min = None;
-        for pair in [[Dir::Up, Dir::Down], [Dir::Left, Dir::Right]] {
-            let pair = pair.map(|d| self.grid.dir_index(idx, d).and_then(|i| self.dists[i]));
-            if let [Some(a), Some(b)] = pair {
-                opt_min(&mut min, a.abs_diff(b));
-            }
-        }
-
-        min.unwrap_or(0).saturating_sub(2)
+    /// Counts the cheats of up to `len` steps saving at least 100
+    fn count_cheats(&self, len: usize) -> usize {
+        let width = self.grid.width();
+        let total = self.fields.total().expect("No path to end found");
+        (0..self.grid.len())
+            .filter(|&idx| self.fields.from_start[idx].is_some())
+            .map(|idx| {
+                self.iter_dist(idx, len)
+                    .filter_map(|(c, diff)| self.fields.through(idx, diff, c.to_idx(width)))
+                    .filter(|&d| total.saturating_sub(d) >= 100)
+                    .count()
+            })
+            .sum()
     }
 }
 
@@ -113,23 +63,29 @@ mod parse {
 
     pub fn parse(input: &[u8]) -> Result<Day20, Error20> {
         let mut start = None;
+        let mut end = None;
         let grid = Grid::parse_co(input, |b, x, y| {
             Some(match b {
-                b'.' | b'E' => false,
+                b'.' => false,
                 b'#' => true,
                 b'S' => {
                     start = Some(Coord { x, y });
                     false
                 }
+                b'E' => {
+                    end = Some(Coord { x, y });
+                    false
+                }
                 _ => return None,
             })
         })
         .context(GridSnafu)?
         .1;
 
+        let start = start.context(StartSnafu)?.to_idx(grid.width());
+        let end = end.context(EndSnafu)?.to_idx(grid.width());
         Ok(Day20 {
-            start: start.context(StartSnafu)?.to_idx(grid.width()),
-            dists: vec![None; grid.len()].into(),
+            fields: grid.dist_fields(start, end, |&wall| !wall),
             grid,
         })
     }
@@ -140,5 +96,7 @@ mod parse {
         Grid { source: GridParseErr },
         #[snafu(display("Start not found in grid"))]
         Start,
+        #[snafu(display("End not found in grid"))]
+        End,
     }
 }
@@ -1,6 +1,5 @@
 use super::Solution;
-use crate::utils::NomFail;
-use enum_map::{Enum, EnumArray, EnumMap};
+use crate::utils::{memo::Memo, NomFail};
 use smallvec::SmallVec;
 
 day!(run 21);
@@ -32,13 +31,13 @@ impl<'i> Solution<'i> for Day21 {
 
 impl Day21 {
     pub fn process_inputs(&self, n: usize) -> u64 {
-        let cache = build_cache(n);
+        let mut memo = CostMemo::new();
         let mut ans = 0;
         for input in &self.codes {
             let mut last = NumPad::Activate;
             let mut sum = 0;
             for button in input.code {
-                sum += cache.get(last, button);
+                sum += calc_dist::<NumPad>(last, button, &mut |a, b| dir_cost(n, a, b, &mut memo));
                 last = button;
             }
             ans += sum * u64::from(input.val);
@@ -47,36 +46,29 @@ impl Day21 {
     }
 }
 
-fn build_cache(n: usize) -> Cache<NumPad> {
-    let mut prev_cache = Cache::default();
-    let mut cur_cache = if n > 0 {
-        Cache::with_dists()
-    } else {
-        Cache::ones()
-    };
-
-    // Build robot cache
-    for _ in 1..n {
-        std::mem::swap(&mut prev_cache, &mut cur_cache);
-        for (a, arr) in &mut cur_cache.map {
-            for (b, dist) in arr {
-                *dist = calc_dist::<DirPad>(a, b, &prev_cache);
-            }
-        }
-    }
+/// Presses needed on the human's keypad to move the first of `robots`
+/// directional robots from `a` to `b` and press it, keyed by robots left
+type CostMemo = Memo<(usize, DirPad, DirPad), u64>;
 
-    // Build door cache
-    let map = EnumMap::from_fn(|a| EnumMap::from_fn(|b| calc_dist::<NumPad>(a, b, &cur_cache)));
+fn dir_cost(robots: usize, a: DirPad, b: DirPad, memo: &mut CostMemo) -> u64 {
+    // The human presses buttons directly
+    let Some(robots) = robots.checked_sub(1) else {
+        return 1;
+    };
 
-    Cache { map }
+    memo.get_or_insert_with((robots, a, b), |memo| {
+        calc_dist::<DirPad>(a, b, &mut |x, y| dir_cost(robots, x, y, memo))
+    })
 }
 
-fn calc_dist<K: Keypad>(src: K, dst: K, cache: &Cache<DirPad>) -> u64 {
+/// Presses needed to move from `src` to `dst` and press it, where `cost`
+/// gives the presses to do the same one keypad up
+fn calc_dist<K: Keypad>(src: K, dst: K, cost: &mut dyn FnMut(DirPad, DirPad) -> u64) -> u64 {
     use DirPad::Activate;
 
     let mut total_dist = 0u64;
-    let mut add = |a, b| total_dist += cache.get(a, b);
-    let path = possible_paths::<K>(src, dst, cache);
+    let path = possible_paths::<K>(src, dst, cost);
+    let mut add = |a, b| total_dist += cost(a, b);
     match path {
         Path::Single { key, dist } => {
             add(Activate, key);
@@ -109,7 +101,7 @@ fn calc_dist<K: Keypad>(src: K, dst: K, cache: &Cache<DirPad>) -> u64 {
 
 // ----------- Path Finding -----------
 
-fn possible_paths<K: Keypad>(srcb: K, dstb: K, cache: &Cache<DirPad>) -> Path {
+fn possible_paths<K: Keypad>(srcb: K, dstb: K, cost: &mut dyn FnMut(DirPad, DirPad) -> u64) -> Path {
     use std::cmp::Ordering::*;
     use DirPad::*;
 
@@ -131,12 +123,12 @@ fn possible_paths<K: Keypad>(srcb: K, dstb: K, cache: &Cache<DirPad>) -> Path {
 
     let contains = |s: u8, d, g| (s.min(d)..=s.max(d)).contains(&g);
 
-    // Avoid gap and consult cache
-    let choose = |x, y| {
+    // Avoid gap and compare costs
+    let mut choose = |x, y| {
         let yfirst = src.y == K::GAP.y && contains(src.x, dst.x, K::GAP.x);
         let xfirst = src.x == K::GAP.x && contains(src.y, dst.y, K::GAP.y);
 
-        if xfirst || (!yfirst && cache.get(x, y) < cache.get(y, x)) {
+        if xfirst || (!yfirst && cost(x, y) < cost(y, x)) {
             Path::double(x, xdist, y, ydist)
         } else {
             Path::double(y, ydist, x, xdist)
@@ -186,48 +178,9 @@ impl Path {
     }
 }
 
-// ------------- CACHE -------------
-
-struct Cache<B: Cachable> {
-    map: EnumMap<B, EnumMap<B, u64>>,
-}
-
-impl<B: Cachable> Cache<B> {
-    pub fn get(&self, a: B, b: B) -> u64 {
-        self.map[a][b]
-    }
-
-    pub fn ones() -> Self {
-        Self {
-            map: EnumMap::from_fn(|_| EnumMap::from_fn(|_| 1)),
-        }
-    }
-}
-
-impl<B: Cachable + Keypad> Cache<B> {
-    pub fn with_dists() -> Self {
-        Self {
-            map: EnumMap::from_fn(|a: B| {
-                EnumMap::from_fn(|b: B| 1 + u64::from(a.coord().dist(b.coord())))
-            }),
-        }
-    }
-}
-
-impl<B: Cachable> Default for Cache<B> {
-    fn default() -> Self {
-        Self {
-            map: EnumMap::default(),
-        }
-    }
-}
-
-trait Cachable: EnumArray<u64> + EnumArray<EnumMap<Self, u64>> + PartialEq {}
-impl<T: Enum + EnumArray<u64> + EnumArray<EnumMap<Self, u64>> + PartialEq> Cachable for T {}
-
 // ------------- BUTTONS -------------
 
-#[derive(Copy, Clone, Debug, Enum, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum DirPad {
     Up,
     Right,
@@ -236,7 +189,7 @@ enum DirPad {
     Activate,
 }
 
-#[derive(Copy, Clone, Debug, Enum, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum NumPad {
     Zero,
     One,
@@ -252,7 +205,7 @@ enum NumPad {
 }
 
 // -------------  KEYPADS -------------
-trait Keypad: Copy + Cachable {
+trait Keypad: Copy {
     /// Location of the board's gap
     const GAP: Coord;
 
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::{prelude::*, utils::sgrid::Dir};
+use crate::{prelude::*, utils::{record::Recorder, render::Rgb, sgrid::Dir}};
 
 use super::Solution;
 
@@ -46,8 +46,13 @@ impl<'i> Solution<'i> for Day6 {
         let mut dir = Dir::Up;
         self.grid[self.start].prev = Some((usize::MAX, Dir::Up));
         let mut count = 1;
+        let mut rec = Recorder::from_args(2).map(|r| r.every(10));
 
         loop {
+            if let Some(rec) = &mut rec {
+                rec.snapshot(&self.grid, Tile::color);
+            }
+
             // Store prevs
             let (pidx, pdir) = (idx, dir);
 
@@ -68,6 +73,10 @@ impl<'i> Solution<'i> for Day6 {
             }
         }
 
+        if let Some(Err(e)) = rec.map(|rec| rec.finish("y2024_day6")) {
+            eprintln!("Failed to save recording: {e}");
+        }
+
         count
     }
 
@@ -150,7 +159,19 @@ enum Grid6Err {
     },
 }
 
+#[derive(Clone)]
 struct Tile {
     block: bool,
     prev: Option<(usize, Dir)>,
 }
+
+impl Tile {
+    /// Obstacles in grey and the guard's path so far in red
+    fn color(self) -> Rgb {
+        match (self.block, self.prev) {
+            (true, _) => [128, 128, 128],
+            (false, Some(_)) => [220, 40, 40],
+            (false, None) => [0, 0, 0],
+        }
+    }
+}
@@ -82,18 +82,11 @@ fn concat(a: u64, b: u64) -> Option<u64> {
 
 
 mod parse {
-    use nom::{bytes::complete::tag, character::complete::{space1, u16, u64}, combinator::all_consuming, multi::many1, sequence::{preceded, terminated, tuple}, Finish, IResult};
     use smallvec::SmallVec;
-    use crate::utils::{parser::{for_each, line}, NomFail};
+    use crate::utils::{parser::parse_lines, NomFail};
     use super::Eqn;
 
-    fn eqn(input: &[u8]) -> IResult<&[u8], Eqn> {
-        let mut nums = SmallVec::new();
-        let (i, result) = line(terminated(u64, tuple((tag(":"), for_each(preceded(space1, u16), |n| nums.push(n))))))(input)?;
-        Ok((i, Eqn { result, nums }))
-    }
-
     pub fn parse(input: &[u8]) -> Result<Vec<Eqn>, NomFail> {
-        Ok(all_consuming(many1(eqn))(input).finish()?.1)
+        parse_lines(input, format_parser!("{}: {}" => Eqn { result: u64, nums: SmallVec<[u16; 12]> }))
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{prelude::*, utils::interval::Interval};
 
 use super::Solution;
 
@@ -11,8 +11,7 @@ struct Day9 {
 #[derive(Copy, Clone)]
 struct Block {
     tag: Option<usize>,
-    len: u8,
-    idx: usize,
+    span: Interval<usize>,
 }
 
 impl<'i> Solution<'i> for Day9 {
@@ -26,10 +25,9 @@ impl<'i> Solution<'i> for Day9 {
                     return Some(BadByteSnafu { b: len }.fail());
                 }
                 let tag = (i % 2 == 0).then_some(i / 2);
-                let idx = *n;
-                let len = len - b'0';
-                *n += usize::from(len);
-                Some(Ok(Block { tag, len, idx }))
+                let span = Interval::with_len(*n, usize::from(len - b'0'));
+                *n = span.end;
+                Some(Ok(Block { tag, span }))
             })
             .try_collect()
             .map(|vec| Self { vec })
@@ -39,7 +37,7 @@ impl<'i> Solution<'i> for Day9 {
         let mut blocks = self
             .vec
             .iter()
-            .flat_map(|b| iter::repeat_n(b.tag, b.len.into()));
+            .flat_map(|b| iter::repeat_n(b.tag, b.span.len()));
 
         let mut i = 0_usize;
         let mut checksum = 0_usize;
@@ -68,7 +66,7 @@ impl<'i> Solution<'i> for Day9 {
 
     fn part2(&mut self) -> usize {
         let blocks = self.vec.as_mut_slice();
-        let csum = |idx, len, tag| (idx..idx + len).map(|j| j * tag).sum::<usize>();
+        let csum = |span: Interval<usize>, tag| (span.start..span.end).map(|j| j * tag).sum::<usize>();
 
         // Place each from back
         let checksum = (0..blocks.len())
@@ -76,18 +74,16 @@ impl<'i> Solution<'i> for Day9 {
             .map(|bi| {
                 let block = blocks[bi];
                 let Some(tag) = block.tag else { return 0 };
+                let len = block.span.len();
                 let gap = blocks[..bi]
                     .iter_mut()
-                    .find(|b| b.tag.is_none() && b.len >= block.len);
+                    .find(|b| b.tag.is_none() && b.span.len() >= len);
                 if let Some(gap) = gap {
-                    let blen = usize::from(block.len);
-                    let chk = csum(gap.idx, blen, tag);
-                    let rem = gap.len - block.len;
-                    gap.len = rem;
-                    gap.idx += blen;
-                    chk
+                    let [used, rest] = gap.span.split_at(gap.span.start + len);
+                    gap.span = rest;
+                    csum(used, tag)
                 } else {
-                    csum(block.idx, usize::from(block.len), tag)
+                    csum(block.span, tag)
                 }
             })
             .sum();
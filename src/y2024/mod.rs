@@ -1,6 +1,7 @@
 #![allow(refining_impl_trait_internal)]
 
 use std::{
+    convert::Infallible,
     error::Error,
     fmt::Display,
     time::{Duration, Instant},
@@ -28,6 +29,16 @@ pub trait Solution<'i>: Sized {
     fn part2(&mut self) -> impl Display {
         "<unimplemented>"
     }
+
+    /// Solve part 1 for solvers which can fail, defaulting to [`Self::part1`]
+    fn try_part1(&mut self) -> Result<impl Display, impl Error + 'static> {
+        Ok::<_, Infallible>(self.part1())
+    }
+
+    /// Solve part 2 for solvers which can fail, defaulting to [`Self::part2`]
+    fn try_part2(&mut self) -> Result<impl Display, impl Error + 'static> {
+        Ok::<_, Infallible>(self.part2())
+    }
 }
 
 pub trait InputSolution {
@@ -49,13 +60,13 @@ pub fn solve<S: InputSolution>() -> Result<(), Whatever> {
 
     // Part 1
     {
-        let (dur, ans) = timeit(|| solver.part1());
+        let (dur, ans) = whatever!(try_timeit(|| solver.try_part1()), "Failed to solve part 1");
         println!("Part 1 ({dur:?}): {ans}");
     }
 
     // Part 2
     {
-        let (dur, ans) = timeit(|| solver.part2());
+        let (dur, ans) = whatever!(try_timeit(|| solver.try_part2()), "Failed to solve part 2");
         println!("Part 2 ({dur:?}): {ans}");
     }
 